@@ -0,0 +1,258 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::{BoxedFuture, HashSet},
+};
+
+use crate::{
+    document::{DocumentError, SplineDocument, DOCUMENT_EXTENSION},
+    spline::{Spline, SplineHandle},
+};
+
+/// Loads `.splines` documents as [`SplineAsset`]s and keeps spawned instances in sync with them.
+pub struct SplineAssetPlugin;
+
+impl Plugin for SplineAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<SplineAsset>()
+            .init_asset_loader::<SplineAssetLoader>()
+            .add_systems(Update, (spawn_spline_assets, despawn_spline_assets));
+    }
+}
+
+/// A spline document loaded through the asset server.
+#[derive(Asset, TypePath, Clone, Debug, Deref)]
+pub struct SplineAsset(pub SplineDocument);
+
+#[derive(Default)]
+pub struct SplineAssetLoader;
+
+impl AssetLoader for SplineAssetLoader {
+    type Asset = SplineAsset;
+    type Settings = ();
+    type Error = DocumentError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<SplineAsset, DocumentError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let document = SplineDocument::from_ron(std::str::from_utf8(&bytes)?)?;
+            Ok(SplineAsset(document))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[DOCUMENT_EXTENSION]
+    }
+}
+
+/// Spawns the splines of a [`SplineAsset`], respawning them whenever the asset is reloaded.
+///
+/// The splines follow the instance's transform and go away along with it.
+#[derive(Bundle, Default)]
+pub struct SplineAssetBundle {
+    pub asset: Handle<SplineAsset>,
+    pub instance: SplineAssetInstance,
+    pub spatial: SpatialBundle,
+}
+
+/// Splines spawned from an entity's [`SplineAsset`].
+///
+/// Their handles and control points are children of the instance. The spline entities
+/// themselves stay at the root, since they draw the curve where the points end up.
+#[derive(Component, Default)]
+pub struct SplineAssetInstance {
+    splines: Vec<Entity>,
+}
+
+impl SplineAssetInstance {
+    pub fn splines(&self) -> &[Entity] {
+        &self.splines
+    }
+}
+
+/// Marks splines spawned for the [`SplineAssetInstance`] on the given entity.
+#[derive(Component)]
+struct SplineAssetSpline(Entity);
+
+fn spawn_spline_assets(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<SplineAsset>>,
+    assets: Res<Assets<SplineAsset>>,
+    mut instances: Query<(Entity, Ref<Handle<SplineAsset>>, &mut SplineAssetInstance)>,
+    splines: Query<&Spline>,
+    handles: Query<&SplineHandle>,
+) {
+    let modified = events
+        .read()
+        .filter_map(|event| match *event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => Some(id),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+
+    for (entity, asset, mut instance) in instances.iter_mut() {
+        if !asset.is_changed() && !modified.contains(&asset.id()) {
+            continue;
+        }
+        let Some(asset) = assets.get(&*asset) else {
+            continue;
+        };
+
+        for spline in instance.splines.drain(..) {
+            despawn_spline(&mut commands, spline, &splines, &handles);
+        }
+        instance.splines = asset
+            .splines
+            .iter()
+            .map(|data| {
+                let spline = data.spawn_in(&mut commands, entity);
+                commands.entity(spline).insert(SplineAssetSpline(entity));
+                spline
+            })
+            .collect();
+    }
+}
+
+/// Cleans up after instances that were despawned or lost their [`SplineAssetInstance`].
+fn despawn_spline_assets(
+    mut commands: Commands,
+    mut removed: RemovedComponents<SplineAssetInstance>,
+    spawned: Query<(Entity, &SplineAssetSpline)>,
+    splines: Query<&Spline>,
+    handles: Query<&SplineHandle>,
+) {
+    let removed = removed.read().collect::<HashSet<_>>();
+    if removed.is_empty() {
+        return;
+    }
+
+    for (spline, instance) in spawned.iter() {
+        if removed.contains(&instance.0) {
+            despawn_spline(&mut commands, spline, &splines, &handles);
+        }
+    }
+}
+
+/// Despawns the spline along with whatever is left of its handles and control points.
+fn despawn_spline(
+    commands: &mut Commands,
+    entity: Entity,
+    splines: &Query<&Spline>,
+    handles: &Query<&SplineHandle>,
+) {
+    let Ok(spline) = splines.get(entity) else {
+        return;
+    };
+    for &e in spline.handles.iter() {
+        let Ok(handle) = handles.get(e) else {
+            continue;
+        };
+        commands.entity(handle.control_point_a).despawn_recursive();
+        commands.entity(handle.control_point_b).despawn_recursive();
+        commands.entity(e).despawn_recursive();
+    }
+    commands.entity(entity).despawn_recursive();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        document::{HandleData, SplineData},
+        spline::{ControlMode, HandleControlMode, SplineControlPoint, SplinePlugin, SplineStyle},
+    };
+
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            // Nothing is loaded from disk, so there's no asset folder to watch.
+            AssetPlugin {
+                watch_for_changes_override: Some(false),
+                ..default()
+            },
+            HierarchyPlugin,
+            TransformPlugin,
+            SplinePlugin,
+            SplineAssetPlugin,
+        ));
+        app
+    }
+
+    fn asset() -> SplineAsset {
+        let handle = |position: Vec2| HandleData {
+            position,
+            control_a: position - Vec2::X,
+            control_b: position + Vec2::X,
+            mode: HandleControlMode::Inherit,
+        };
+        SplineAsset(SplineDocument {
+            splines: vec![SplineData {
+                name: None,
+                closed: false,
+                control_mode: ControlMode::Free,
+                style: SplineStyle::default(),
+                handles: vec![handle(Vec2::ZERO), handle(Vec2::new(10., 0.))],
+            }],
+            ..default()
+        })
+    }
+
+    fn count<F: bevy::ecs::query::QueryFilter>(app: &mut App) -> usize {
+        app.world.query_filtered::<(), F>().iter(&app.world).count()
+    }
+
+    #[test]
+    fn splines_follow_the_instance() {
+        let mut app = app();
+        let asset = app.world.resource_mut::<Assets<SplineAsset>>().add(asset());
+        let instance = app
+            .world
+            .spawn(SplineAssetBundle {
+                asset,
+                spatial: SpatialBundle::from_transform(Transform::from_xyz(100., 50., 0.)),
+                ..default()
+            })
+            .id();
+        app.update();
+
+        let spline = app
+            .world
+            .get::<SplineAssetInstance>(instance)
+            .unwrap()
+            .splines()[0];
+        let handle = app.world.get::<Spline>(spline).unwrap().handles[1];
+        let position = app
+            .world
+            .get::<GlobalTransform>(handle)
+            .unwrap()
+            .translation();
+        assert_eq!(position.truncate(), Vec2::new(110., 50.));
+    }
+
+    #[test]
+    fn despawning_the_instance_despawns_its_splines() {
+        let mut app = app();
+        let asset = app.world.resource_mut::<Assets<SplineAsset>>().add(asset());
+        let instance = app
+            .world
+            .spawn(SplineAssetBundle { asset, ..default() })
+            .id();
+        app.update();
+        assert_eq!(count::<With<Spline>>(&mut app), 1);
+        assert_eq!(count::<With<SplineHandle>>(&mut app), 2);
+
+        app.world.entity_mut(instance).despawn_recursive();
+        app.update();
+        assert_eq!(count::<With<Spline>>(&mut app), 0);
+        assert_eq!(count::<With<SplineHandle>>(&mut app), 0);
+        assert_eq!(count::<With<SplineControlPoint>>(&mut app), 0);
+    }
+}
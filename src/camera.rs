@@ -13,6 +13,11 @@ use bevy_pancam::{PanCam, PanCamPlugin};
 pub const MAIN_RENDER_LAYER: RenderLayers = RenderLayers::layer(0);
 pub const GRID_RENDER_LAYER: RenderLayers = RenderLayers::layer(1);
 
+/// Smallest cell the grid shader draws, and how wide in pixels a cell has to be before it shows.
+/// Keep these in sync with `grid.wgsl`.
+const GRID_MIN_CELL_SIZE: f32 = 0.0001;
+const GRID_MIN_CELL_PIXEL_WIDTH: f32 = 2.;
+
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
@@ -25,7 +30,7 @@ impl Plugin for CameraPlugin {
 }
 
 #[derive(Component)]
-struct MainCamera;
+pub struct MainCamera;
 
 #[derive(Component)]
 struct Grid;
@@ -46,7 +51,11 @@ fn setup_cameras(
                 },
                 ..default()
             },
-            PanCam::default(),
+            // Left and right drags are taken by the editor for selection.
+            PanCam {
+                grab_buttons: vec![MouseButton::Middle],
+                ..default()
+            },
         ))
         .with_children(|parent| {
             parent.spawn((
@@ -131,6 +140,20 @@ fn sync_grid_size(
     );
 }
 
+/// Spacing of the finest grid lines currently visible, given how many world units a physical
+/// pixel covers. Follows the shader's level of detail, switching to the next coarser level once
+/// the finer lines have faded out more than halfway.
+pub fn grid_spacing(world_per_pixel: f32) -> f32 {
+    let lod =
+        ((world_per_pixel * GRID_MIN_CELL_PIXEL_WIDTH / GRID_MIN_CELL_SIZE).log10() + 1.).max(0.);
+    let spacing = GRID_MIN_CELL_SIZE * 10_f32.powf(lod.floor() + 1.);
+    if lod.fract() < 0.5 {
+        spacing
+    } else {
+        spacing * 10.
+    }
+}
+
 #[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
 struct GridMaterial {}
 
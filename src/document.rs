@@ -0,0 +1,359 @@
+use std::{fs, path::Path};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    geometry::{HandlePoints, SplineCurve},
+    spline::{
+        ControlMode, HandleControlMode, Spline, SplineBundle, SplineControlPoint,
+        SplineControlPointBundle, SplineHandle, SplineHandleBundle, SplineStyle,
+    },
+};
+
+/// Version written to new documents. Bump it whenever the format changes in a way older
+/// readers can't handle.
+pub const DOCUMENT_VERSION: u32 = 1;
+
+pub const DOCUMENT_EXTENSION: &str = "splines";
+
+#[derive(Debug, Error)]
+pub enum DocumentError {
+    #[error("could not access document: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("document is not valid UTF-8: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+    #[error("could not parse document: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("could not write document: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("document version {0} is newer than the supported version {DOCUMENT_VERSION}")]
+    UnsupportedVersion(u32),
+}
+
+/// A set of splines as stored in `.splines` files.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SplineDocument {
+    pub version: u32,
+    #[serde(default)]
+    pub metadata: DocumentMetadata,
+    #[serde(default)]
+    pub splines: Vec<SplineData>,
+}
+
+impl Default for SplineDocument {
+    fn default() -> Self {
+        Self {
+            version: DOCUMENT_VERSION,
+            metadata: default(),
+            splines: Vec::new(),
+        }
+    }
+}
+
+#[derive(Resource, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DocumentMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SplineData {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub closed: bool,
+    #[serde(default)]
+    pub control_mode: ControlMode,
+    #[serde(default)]
+    pub style: SplineStyle,
+    pub handles: Vec<HandleData>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HandleData {
+    pub position: Vec2,
+    pub control_a: Vec2,
+    pub control_b: Vec2,
+    #[serde(default)]
+    pub mode: HandleControlMode,
+}
+
+/// Just enough of a document to check its version before parsing the rest.
+#[derive(Deserialize)]
+#[serde(rename = "SplineDocument")]
+struct DocumentHeader {
+    version: u32,
+}
+
+impl SplineDocument {
+    pub fn from_ron(ron: &str) -> Result<Self, DocumentError> {
+        let header: DocumentHeader = ron::from_str(ron)?;
+        if header.version > DOCUMENT_VERSION {
+            return Err(DocumentError::UnsupportedVersion(header.version));
+        }
+
+        Ok(ron::from_str(ron)?)
+    }
+
+    pub fn to_ron(&self) -> Result<String, DocumentError> {
+        Ok(ron::ser::to_string_pretty(
+            self,
+            PrettyConfig::default().struct_names(true),
+        )?)
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, DocumentError> {
+        Self::from_ron(&fs::read_to_string(path)?)
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), DocumentError> {
+        Ok(fs::write(path, self.to_ron()?)?)
+    }
+
+    /// Spawns every spline in the document, returning the spline entities.
+    pub fn spawn(&self, commands: &mut Commands) -> Vec<Entity> {
+        self.splines.iter().map(|s| s.spawn(commands)).collect()
+    }
+}
+
+impl SplineData {
+    pub fn curve(&self) -> SplineCurve {
+        let handles = self
+            .handles
+            .iter()
+            .map(|h| HandlePoints {
+                position: h.position,
+                control_a: h.control_a,
+                control_b: h.control_b,
+            })
+            .collect::<Vec<_>>();
+        SplineCurve::from_handles(&handles, self.closed)
+    }
+
+    /// Spawns the spline along with its handles and control points.
+    pub fn spawn(&self, commands: &mut Commands) -> Entity {
+        self.spawn_with_parent(commands, None)
+    }
+
+    /// Like [`SplineData::spawn`], but with the handles and control points as children of
+    /// `parent`, so the spline follows its transform.
+    pub fn spawn_in(&self, commands: &mut Commands, parent: Entity) -> Entity {
+        self.spawn_with_parent(commands, Some(parent))
+    }
+
+    fn spawn_with_parent(&self, commands: &mut Commands, parent: Option<Entity>) -> Entity {
+        let handles = self
+            .handles
+            .iter()
+            .map(|data| {
+                let handle = commands.spawn_empty().id();
+                let control_point_a = commands
+                    .spawn(SplineControlPointBundle::new(data.control_a, handle))
+                    .id();
+                let control_point_b = commands
+                    .spawn(SplineControlPointBundle::new(data.control_b, handle))
+                    .id();
+
+                let mut bundle = SplineHandleBundle::new(
+                    SplineHandle {
+                        control_point_a,
+                        control_point_b,
+                    },
+                    data.position,
+                );
+                bundle.handle_control_mode = data.mode;
+                commands.entity(handle).insert(bundle);
+                if let Some(parent) = parent {
+                    commands.entity(parent).push_children(&[
+                        handle,
+                        control_point_a,
+                        control_point_b,
+                    ]);
+                }
+                handle
+            })
+            .collect();
+
+        let mut spline = commands.spawn(SplineBundle {
+            spline: Spline {
+                handles,
+                closed: self.closed,
+            },
+            control_mode: self.control_mode,
+            style: self.style,
+            ..default()
+        });
+        if let Some(name) = &self.name {
+            spline.insert(Name::new(name.clone()));
+        }
+        spline.id()
+    }
+}
+
+/// Reads the splines in the world back into a [`SplineDocument`].
+#[derive(SystemParam)]
+pub struct DocumentCapture<'w, 's> {
+    splines: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Spline,
+            &'static ControlMode,
+            &'static SplineStyle,
+            Option<&'static Name>,
+        ),
+    >,
+    handles: Query<
+        'w,
+        's,
+        (
+            &'static Transform,
+            &'static SplineHandle,
+            &'static HandleControlMode,
+        ),
+    >,
+    control_points:
+        Query<'w, 's, &'static Transform, (With<SplineControlPoint>, Without<SplineHandle>)>,
+}
+
+impl<'w, 's> DocumentCapture<'w, 's> {
+    pub fn spline(&self, entity: Entity) -> Option<SplineData> {
+        let (_, spline, &control_mode, &style, name) = self.splines.get(entity).ok()?;
+        let position = |entity: Entity| {
+            self.control_points
+                .get(entity)
+                .ok()
+                .map(|t| t.translation.truncate())
+        };
+
+        let handles = spline
+            .handles
+            .iter()
+            .map(|&e| {
+                let (transform, handle, &mode) = self.handles.get(e).ok()?;
+                Some(HandleData {
+                    position: transform.translation.truncate(),
+                    control_a: position(handle.control_point_a)?,
+                    control_b: position(handle.control_point_b)?,
+                    mode,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(SplineData {
+            name: name.map(|n| n.as_str().to_owned()),
+            closed: spline.closed,
+            control_mode,
+            style,
+            handles,
+        })
+    }
+
+    /// Captures all splines, ordered by entity so saving the same scene twice gives the same file.
+    pub fn document(&self, metadata: DocumentMetadata) -> SplineDocument {
+        let mut entities = self.splines.iter().map(|(e, ..)| e).collect::<Vec<_>>();
+        entities.sort();
+
+        SplineDocument {
+            version: DOCUMENT_VERSION,
+            metadata,
+            splines: entities
+                .into_iter()
+                .filter_map(|e| self.spline(e))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document() -> SplineDocument {
+        SplineDocument {
+            version: DOCUMENT_VERSION,
+            metadata: DocumentMetadata {
+                title: Some("Track".into()),
+                author: None,
+                description: Some("A \"quoted\" description".into()),
+            },
+            splines: vec![
+                SplineData {
+                    name: Some("Outline".into()),
+                    closed: true,
+                    control_mode: ControlMode::Aligned,
+                    style: SplineStyle {
+                        fill: Some(Color::rgb(0.2, 0.4, 0.6)),
+                        stroke: None,
+                        stroke_width: 3.5,
+                    },
+                    handles: vec![
+                        HandleData {
+                            position: Vec2::new(0., 0.),
+                            control_a: Vec2::new(-10., 5.),
+                            control_b: Vec2::new(10., -5.),
+                            mode: HandleControlMode::Inherit,
+                        },
+                        HandleData {
+                            position: Vec2::new(100., 50.),
+                            control_a: Vec2::new(90., 40.),
+                            control_b: Vec2::new(110., 60.),
+                            mode: HandleControlMode::Custom(ControlMode::Free),
+                        },
+                    ],
+                },
+                SplineData {
+                    name: None,
+                    closed: false,
+                    control_mode: ControlMode::Vector,
+                    style: SplineStyle::default(),
+                    handles: Vec::new(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn ron_round_trip() {
+        let document = document();
+        let ron = document.to_ron().unwrap();
+        assert_eq!(SplineDocument::from_ron(&ron).unwrap(), document);
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let document = SplineDocument::from_ron("SplineDocument(version: 1)").unwrap();
+        assert_eq!(document, SplineDocument::default());
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let mut document = document();
+        document.version = DOCUMENT_VERSION + 1;
+        let ron = document.to_ron().unwrap();
+
+        assert!(matches!(
+            SplineDocument::from_ron(&ron),
+            Err(DocumentError::UnsupportedVersion(v)) if v == DOCUMENT_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn checks_version_before_parsing_the_rest() {
+        // A future version may change the layout entirely, which should still report the version.
+        let ron = format!(
+            "SplineDocument(version: {}, layers: [])",
+            DOCUMENT_VERSION + 1
+        );
+        assert!(matches!(
+            SplineDocument::from_ron(&ron),
+            Err(DocumentError::UnsupportedVersion(_))
+        ));
+    }
+}
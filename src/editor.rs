@@ -1,8 +1,39 @@
-use bevy::{input::InputSystem, prelude::*, window::PrimaryWindow};
+use bevy::{input::InputSystem, prelude::*, utils::HashSet, window::PrimaryWindow};
+use bevy_vello::{VelloScene, VelloSceneBundle};
 use leafwing_input_manager::{
     axislike::DualAxisData, plugin::InputManagerSystem, prelude::*, systems::run_if_enabled,
 };
 
+use crate::spline::{ControlMode, HandleControlMode, Spline, SplineControlPoint, SplineHandle};
+
+mod clipboard;
+mod edit;
+mod file;
+mod handles;
+mod history;
+mod pen;
+mod pencil;
+mod play;
+mod selection;
+mod snap;
+mod transform;
+
+pub use file::DocumentPath;
+pub use pencil::PencilSettings;
+pub use snap::SnapSettings;
+
+use clipboard::ClipboardPlugin;
+use edit::EditPlugin;
+use file::FilePlugin;
+use handles::HandleRenderPlugin;
+use history::{Change, HistoryPlugin, RecordEdit, Snapshots, SplineChange};
+use pen::PenPlugin;
+use pencil::PencilPlugin;
+use play::PlayPlugin;
+use selection::SelectionPlugin;
+use snap::SnapPlugin;
+use transform::ModalTransformPlugin;
+
 pub struct EditorPlugin;
 
 impl Plugin for EditorPlugin {
@@ -12,11 +43,40 @@ impl Plugin for EditorPlugin {
             .register_type::<EditCursorStart>()
             .add_plugins(InputManagerPlugin::<EditorAction>::default())
             .add_plugins(InputManagerPlugin::<SelectAction>::default())
+            .add_plugins(InputManagerPlugin::<TransformAction>::default())
+            .add_plugins(InputManagerPlugin::<PenAction>::default())
+            .add_plugins(InputManagerPlugin::<PencilAction>::default())
+            .add_plugins(InputManagerPlugin::<PlayAction>::default())
+            .add_plugins((
+                ClipboardPlugin,
+                EditPlugin,
+                FilePlugin,
+                HandleRenderPlugin,
+                HistoryPlugin,
+                PenPlugin,
+                PencilPlugin,
+                PlayPlugin,
+                SelectionPlugin,
+                SnapPlugin,
+                ModalTransformPlugin,
+            ))
             .init_resource::<ActionState<EditorAction>>()
             .init_resource::<ActionState<SelectAction>>()
+            .init_resource::<ActionState<TransformAction>>()
+            .init_resource::<ActionState<PenAction>>()
+            .init_resource::<ActionState<PencilAction>>()
+            .init_resource::<ActionState<PlayAction>>()
             .insert_resource(EditorAction::default_input_map())
             .insert_resource(SelectAction::default_input_map())
+            .insert_resource(TransformAction::default_input_map())
+            .insert_resource(PenAction::default_input_map())
+            .insert_resource(PencilAction::default_input_map())
+            .insert_resource(PlayAction::default_input_map())
             .insert_resource(EditCursorStart(None))
+            .init_resource::<Status>()
+            .add_systems(Startup, (setup_overlay, setup_status_bar))
+            .add_systems(PostUpdate, update_status_bar)
+            .add_systems(PreUpdate, clear_overlay)
             .add_systems(
                 Update,
                 update_cursor_state_from_window
@@ -31,10 +91,8 @@ impl Plugin for EditorPlugin {
                 Update,
                 (
                     update_edit_cursor_start,
-                    (
-                        update_select.run_if(in_state(EditorState::Select)),
-                        move_selected.run_if(in_state(EditorState::Move)),
-                    ),
+                    (update_select, toggle_closed, cycle_control_mode)
+                        .run_if(in_state(EditorState::Select)),
                 )
                     .chain(),
             );
@@ -48,48 +106,279 @@ enum EditorState {
     Move,
     Rotate,
     Scale,
+    Pen,
+    Pencil,
+    Play,
 }
 
 #[derive(Actionlike, Clone, Debug, Copy, PartialEq, Eq, Hash, Reflect)]
 enum EditorAction {
     MousePosition,
+    /// Held to flip whether snapping is on.
+    InvertSnap,
 }
 
 impl EditorAction {
     fn default_input_map() -> InputMap<Self> {
         let mut input_map = InputMap::default();
 
+        input_map.insert(Self::InvertSnap, Modifier::Control);
+
         input_map
     }
 }
 
 #[derive(Actionlike, Clone, Debug, Copy, PartialEq, Eq, Hash, Reflect)]
 enum SelectAction {
+    Select,
     Deselect,
     Move,
     Rotate,
     Scale,
+    AddToSelection,
+    SubtractFromSelection,
+    Lasso,
+    CycleSelectionFilter,
+    PlaceCursor,
+    CyclePivot,
+    ToggleClosed,
+    CycleControlMode,
+    InsertHandle,
+    Delete,
+    Dissolve,
+    Duplicate,
+    Copy,
+    Paste,
+    Undo,
+    Redo,
+    Save,
+    SaveAs,
+    Open,
+    Import,
+    Export,
+    Pen,
+    Pencil,
+    Play,
+    ToggleSnap,
+    ToggleGridSnap,
+    TogglePointSnap,
+    ToggleMidpointSnap,
+    ToggleExtremumSnap,
+    ToggleIntersectionSnap,
+    ToggleAngleSnap,
 }
 
 impl SelectAction {
     fn default_input_map() -> InputMap<Self> {
         let mut input_map = InputMap::default();
 
+        input_map.insert(Self::Select, MouseButton::Left);
         input_map.insert(Self::Deselect, KeyCode::Escape);
         input_map.insert(Self::Move, KeyCode::KeyG);
         input_map.insert(Self::Rotate, KeyCode::KeyR);
         input_map.insert(Self::Scale, KeyCode::KeyS);
+        input_map.insert(Self::AddToSelection, Modifier::Shift);
+        input_map.insert(Self::SubtractFromSelection, Modifier::Control);
+        input_map.insert(Self::Lasso, MouseButton::Right);
+        input_map.insert(Self::CycleSelectionFilter, KeyCode::Tab);
+        input_map.insert(
+            Self::PlaceCursor,
+            UserInput::modified(Modifier::Shift, KeyCode::KeyC),
+        );
+        input_map.insert(Self::CyclePivot, KeyCode::Period);
+        input_map.insert(
+            Self::ToggleClosed,
+            UserInput::modified(Modifier::Alt, KeyCode::KeyC),
+        );
+        input_map.insert(Self::CycleControlMode, KeyCode::KeyV);
+        input_map.insert(
+            Self::InsertHandle,
+            UserInput::modified(Modifier::Alt, MouseButton::Left),
+        );
+        input_map.insert(Self::Delete, KeyCode::Delete);
+        input_map.insert(Self::Delete, KeyCode::KeyX);
+        input_map.insert(
+            Self::Dissolve,
+            UserInput::modified(Modifier::Control, KeyCode::Delete),
+        );
+        input_map.insert(
+            Self::Dissolve,
+            UserInput::modified(Modifier::Control, KeyCode::KeyX),
+        );
+        input_map.insert(
+            Self::Duplicate,
+            UserInput::modified(Modifier::Shift, KeyCode::KeyD),
+        );
+        input_map.insert(
+            Self::Copy,
+            UserInput::modified(Modifier::Control, KeyCode::KeyC),
+        );
+        input_map.insert(
+            Self::Paste,
+            UserInput::modified(Modifier::Control, KeyCode::KeyV),
+        );
+        input_map.insert(
+            Self::Undo,
+            UserInput::modified(Modifier::Control, KeyCode::KeyZ),
+        );
+        input_map.insert(
+            Self::Redo,
+            UserInput::chord([
+                InputKind::from(Modifier::Control),
+                InputKind::from(Modifier::Shift),
+                InputKind::from(KeyCode::KeyZ),
+            ]),
+        );
+        input_map.insert(
+            Self::Redo,
+            UserInput::modified(Modifier::Control, KeyCode::KeyY),
+        );
+        input_map.insert(
+            Self::Save,
+            UserInput::modified(Modifier::Control, KeyCode::KeyS),
+        );
+        input_map.insert(
+            Self::SaveAs,
+            UserInput::chord([
+                InputKind::from(Modifier::Control),
+                InputKind::from(Modifier::Shift),
+                InputKind::from(KeyCode::KeyS),
+            ]),
+        );
+        input_map.insert(
+            Self::Open,
+            UserInput::modified(Modifier::Control, KeyCode::KeyO),
+        );
+        input_map.insert(
+            Self::Import,
+            UserInput::modified(Modifier::Control, KeyCode::KeyI),
+        );
+        input_map.insert(
+            Self::Export,
+            UserInput::modified(Modifier::Control, KeyCode::KeyE),
+        );
+        input_map.insert(Self::Pen, KeyCode::KeyP);
+        input_map.insert(
+            Self::Pencil,
+            UserInput::modified(Modifier::Shift, KeyCode::KeyP),
+        );
+        input_map.insert(Self::Play, KeyCode::F5);
+        input_map.insert(
+            Self::ToggleSnap,
+            UserInput::modified(Modifier::Shift, KeyCode::Tab),
+        );
+        input_map.insert(
+            Self::ToggleGridSnap,
+            UserInput::modified(Modifier::Alt, KeyCode::Digit1),
+        );
+        input_map.insert(
+            Self::TogglePointSnap,
+            UserInput::modified(Modifier::Alt, KeyCode::Digit2),
+        );
+        input_map.insert(
+            Self::ToggleMidpointSnap,
+            UserInput::modified(Modifier::Alt, KeyCode::Digit3),
+        );
+        input_map.insert(
+            Self::ToggleExtremumSnap,
+            UserInput::modified(Modifier::Alt, KeyCode::Digit4),
+        );
+        input_map.insert(
+            Self::ToggleIntersectionSnap,
+            UserInput::modified(Modifier::Alt, KeyCode::Digit5),
+        );
+        input_map.insert(
+            Self::ToggleAngleSnap,
+            UserInput::modified(Modifier::Alt, KeyCode::Digit6),
+        );
 
         input_map
     }
 }
 
 #[derive(Actionlike, Clone, Debug, Copy, PartialEq, Eq, Hash, Reflect)]
-enum MoveAction {
+enum TransformAction {
     Revert,
     Commit,
     ConstrainToX,
     ConstrainToY,
+    Snap,
+}
+
+impl TransformAction {
+    fn default_input_map() -> InputMap<Self> {
+        let mut input_map = InputMap::default();
+
+        input_map.insert(Self::Revert, KeyCode::Escape);
+        input_map.insert(Self::Revert, MouseButton::Right);
+        input_map.insert(Self::Commit, KeyCode::Enter);
+        input_map.insert(Self::Commit, KeyCode::NumpadEnter);
+        input_map.insert(Self::Commit, MouseButton::Left);
+        input_map.insert(Self::ConstrainToX, KeyCode::KeyX);
+        input_map.insert(Self::ConstrainToY, KeyCode::KeyY);
+        input_map.insert(Self::Snap, Modifier::Control);
+
+        input_map
+    }
+}
+
+#[derive(Actionlike, Clone, Debug, Copy, PartialEq, Eq, Hash, Reflect)]
+enum PenAction {
+    Place,
+    Finish,
+    Cancel,
+}
+
+impl PenAction {
+    fn default_input_map() -> InputMap<Self> {
+        let mut input_map = InputMap::default();
+
+        input_map.insert(Self::Place, MouseButton::Left);
+        input_map.insert(Self::Finish, KeyCode::Enter);
+        input_map.insert(Self::Finish, KeyCode::NumpadEnter);
+        input_map.insert(Self::Cancel, KeyCode::Escape);
+
+        input_map
+    }
+}
+
+#[derive(Actionlike, Clone, Debug, Copy, PartialEq, Eq, Hash, Reflect)]
+enum PencilAction {
+    Draw,
+    IncreaseTolerance,
+    DecreaseTolerance,
+    Exit,
+}
+
+impl PencilAction {
+    fn default_input_map() -> InputMap<Self> {
+        let mut input_map = InputMap::default();
+
+        input_map.insert(Self::Draw, MouseButton::Left);
+        input_map.insert(Self::IncreaseTolerance, KeyCode::BracketRight);
+        input_map.insert(Self::DecreaseTolerance, KeyCode::BracketLeft);
+        input_map.insert(Self::Exit, KeyCode::Escape);
+
+        input_map
+    }
+}
+
+#[derive(Actionlike, Clone, Debug, Copy, PartialEq, Eq, Hash, Reflect)]
+enum PlayAction {
+    DropBody,
+    Stop,
+}
+
+impl PlayAction {
+    fn default_input_map() -> InputMap<Self> {
+        let mut input_map = InputMap::default();
+
+        input_map.insert(Self::DropBody, MouseButton::Left);
+        input_map.insert(Self::Stop, KeyCode::Escape);
+        input_map.insert(Self::Stop, KeyCode::F5);
+
+        input_map
+    }
 }
 
 fn update_select(
@@ -98,33 +387,105 @@ fn update_select(
 ) {
     if action_state.just_pressed(&SelectAction::Move) {
         next_edit_state.set(EditorState::Move);
+    } else if action_state.just_pressed(&SelectAction::Rotate) {
+        next_edit_state.set(EditorState::Rotate);
+    } else if action_state.just_pressed(&SelectAction::Scale) {
+        next_edit_state.set(EditorState::Scale);
     }
 }
 
-fn move_selected(
-    mut selected: Query<(Entity, &mut Transform, Option<&Moving>), With<Selected>>,
-    action_state: Res<ActionState<EditorAction>>,
-    cursor_start: Res<EditCursorStart>,
+/// Closes open splines, or opens closed ones at their first selected handle.
+fn toggle_closed(
     mut commands: Commands,
+    action_state: Res<ActionState<SelectAction>>,
+    splines: Query<(Entity, &Spline)>,
+    snapshots: Snapshots,
+    selected_handles: Query<Entity, (With<SplineHandle>, With<Selected>)>,
+    selected_control_points: Query<&SplineControlPoint, With<Selected>>,
 ) {
-    let cursor_start = cursor_start.0.unwrap_or_default();
-    let cursor_pos = action_state
-        .axis_pair(&EditorAction::MousePosition)
-        .map(|d| d.xy())
-        .unwrap_or_default();
-    let delta = cursor_pos - cursor_start;
-    for (entity, mut transform, maybe_moving) in selected.iter_mut() {
-        let start = match maybe_moving {
-            Some(Moving { start_pos }) => *start_pos,
-            None => {
-                let start_pos = transform.translation.xy();
-                commands.entity(entity).insert(Moving { start_pos });
-                start_pos
+    if !action_state.just_pressed(&SelectAction::ToggleClosed) {
+        return;
+    }
+
+    let selected = selected_handles
+        .iter()
+        .chain(selected_control_points.iter().map(|c| c.handle))
+        .collect::<HashSet<_>>();
+
+    let changes = splines
+        .iter()
+        .filter_map(|(entity, spline)| {
+            let &handle = spline.handles.iter().find(|h| selected.contains(*h))?;
+            let before = snapshots.capture(entity)?;
+            let mut after = before.clone();
+            if after.closed {
+                after.open_at(handle);
+            } else {
+                after.closed = true;
+            }
+            Some(SplineChange {
+                before: Some(before),
+                after: Some(after),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if !changes.is_empty() {
+        commands.add(RecordEdit(Change::Splines(changes)));
+    }
+}
+
+/// Steps the selected handles through Inherit, Free, Aligned, Vector and Automatic.
+fn cycle_control_mode(
+    mut commands: Commands,
+    action_state: Res<ActionState<SelectAction>>,
+    splines: Query<Entity, With<Spline>>,
+    snapshots: Snapshots,
+    selected_handles: Query<Entity, (With<SplineHandle>, With<Selected>)>,
+    selected_control_points: Query<&SplineControlPoint, With<Selected>>,
+) {
+    if !action_state.just_pressed(&SelectAction::CycleControlMode) {
+        return;
+    }
+
+    let selected = selected_handles
+        .iter()
+        .chain(selected_control_points.iter().map(|c| c.handle))
+        .collect::<HashSet<_>>();
+
+    let changes = splines
+        .iter()
+        .filter_map(|entity| {
+            let before = snapshots.capture(entity)?;
+            let mut after = before.clone();
+            for handle in after
+                .handles
+                .iter_mut()
+                .filter(|h| selected.contains(&h.entity))
+            {
+                handle.mode = match handle.mode {
+                    HandleControlMode::Inherit => HandleControlMode::Custom(ControlMode::Free),
+                    HandleControlMode::Custom(ControlMode::Free) => {
+                        HandleControlMode::Custom(ControlMode::Aligned)
+                    }
+                    HandleControlMode::Custom(ControlMode::Aligned) => {
+                        HandleControlMode::Custom(ControlMode::Vector)
+                    }
+                    HandleControlMode::Custom(ControlMode::Vector) => {
+                        HandleControlMode::Custom(ControlMode::Automatic)
+                    }
+                    HandleControlMode::Custom(ControlMode::Automatic) => HandleControlMode::Inherit,
+                };
             }
-        };
-        let new_pos = start + delta;
-        transform.translation.x = new_pos.x;
-        transform.translation.y = new_pos.y;
+            (after != before).then_some(SplineChange {
+                before: Some(before),
+                after: Some(after),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if !changes.is_empty() {
+        commands.add(RecordEdit(Change::Splines(changes)));
     }
 }
 
@@ -134,9 +495,7 @@ fn update_edit_cursor_start(
     action_state: Res<ActionState<EditorAction>>,
 ) {
     if !events.is_empty() {
-        edit_cursor_start.0 = action_state
-            .axis_pair(&EditorAction::MousePosition)
-            .map(|d| d.xy());
+        edit_cursor_start.0 = cursor_position(&action_state);
     }
 }
 
@@ -147,9 +506,71 @@ struct EditCursorStart(Option<Vec2>);
 #[derive(Component)]
 pub struct Selected;
 
+/// World-space vello scene for transient editor feedback like selection regions, cleared at
+/// the start of every frame.
 #[derive(Component)]
-pub struct Moving {
-    start_pos: Vec2,
+struct Overlay;
+
+fn setup_overlay(mut commands: Commands) {
+    commands.spawn((
+        Overlay,
+        VelloSceneBundle {
+            transform: Transform::from_xyz(0., 0., 100.),
+            ..default()
+        },
+    ));
+}
+
+fn clear_overlay(mut overlay: Query<&mut VelloScene, With<Overlay>>) {
+    for mut scene in overlay.iter_mut() {
+        scene.reset();
+    }
+}
+
+/// Text shown in the status bar at the bottom of the window.
+#[derive(Resource, Default)]
+struct Status(String);
+
+#[derive(Component)]
+struct StatusBar;
+
+fn setup_status_bar(mut commands: Commands) {
+    commands.spawn((
+        StatusBar,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 16.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(12.),
+            bottom: Val::Px(8.),
+            ..default()
+        }),
+    ));
+}
+
+fn update_status_bar(status: Res<Status>, mut status_bar: Query<&mut Text, With<StatusBar>>) {
+    if !status.is_changed() {
+        return;
+    }
+
+    for mut text in status_bar.iter_mut() {
+        if let Some(section) = text.sections.first_mut() {
+            section.value.clone_from(&status.0);
+        }
+    }
+}
+
+/// World-space cursor position, as last written by `update_cursor_state_from_window`.
+fn cursor_position(action_state: &ActionState<EditorAction>) -> Option<Vec2> {
+    action_state
+        .axis_pair(&EditorAction::MousePosition)
+        .map(|d| d.xy())
 }
 
 fn update_cursor_state_from_window(
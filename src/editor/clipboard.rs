@@ -0,0 +1,215 @@
+use arboard::Clipboard;
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashSet};
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+
+use crate::{
+    document::{DocumentCapture, SplineDocument},
+    spline::{Spline, SplineControlPoint, SplineHandle},
+    svg,
+};
+
+use super::{
+    history::{BeginInteraction, Change, RecordEdit, Snapshots, SplineChange, SplineSnapshot},
+    EditorState, SelectAction, Selected, Status,
+};
+
+/// Duplicates splines and moves them through the system clipboard.
+pub struct ClipboardPlugin;
+
+impl Plugin for ClipboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_non_send_resource::<SystemClipboard>().add_systems(
+            Update,
+            (duplicate_selected, copy_selected, paste)
+                .run_if(in_state(EditorState::Select))
+                .after(InputManagerSystem::ManualControl),
+        );
+    }
+}
+
+/// Kept open for the whole session, since on some platforms the copied contents go away along
+/// with the clipboard handle.
+struct SystemClipboard(Option<Clipboard>);
+
+impl Default for SystemClipboard {
+    fn default() -> Self {
+        match Clipboard::new() {
+            Ok(clipboard) => Self(Some(clipboard)),
+            Err(err) => {
+                warn!("System clipboard is unavailable: {err}");
+                Self(None)
+            }
+        }
+    }
+}
+
+/// Splines with at least one selected handle or control point.
+#[derive(SystemParam)]
+struct SelectedSplines<'w, 's> {
+    splines: Query<'w, 's, (Entity, &'static Spline)>,
+    handles: Query<'w, 's, Entity, (With<SplineHandle>, With<Selected>)>,
+    control_points: Query<'w, 's, &'static SplineControlPoint, With<Selected>>,
+}
+
+impl<'w, 's> SelectedSplines<'w, 's> {
+    /// Ordered by entity, so copies come out in the same order every time.
+    fn entities(&self) -> Vec<Entity> {
+        let selected = self
+            .handles
+            .iter()
+            .chain(self.control_points.iter().map(|c| c.handle))
+            .collect::<HashSet<_>>();
+
+        let mut entities = self
+            .splines
+            .iter()
+            .filter(|(_, spline)| spline.handles.iter().any(|h| selected.contains(h)))
+            .map(|(e, _)| e)
+            .collect::<Vec<_>>();
+        entities.sort();
+        entities
+    }
+}
+
+/// Copies the selected splines in place and starts moving the copies. Committing the move makes
+/// it part of the same undo step, so undoing removes the copies in one go.
+fn duplicate_selected(
+    mut commands: Commands,
+    action_state: Res<ActionState<SelectAction>>,
+    selected_splines: SelectedSplines,
+    snapshots: Snapshots,
+    selected: Query<Entity, With<Selected>>,
+    mut next_state: ResMut<NextState<EditorState>>,
+) {
+    if !action_state.just_pressed(&SelectAction::Duplicate) {
+        return;
+    }
+
+    let copies = selected_splines
+        .entities()
+        .into_iter()
+        .filter_map(|e| snapshots.capture(e))
+        .map(|snapshot| snapshot.duplicate(&mut commands))
+        .collect::<Vec<_>>();
+    if copies.is_empty() {
+        return;
+    }
+
+    // The move that follows joins this interaction, so it's part of the same undo step.
+    commands.add(BeginInteraction);
+    add_splines(&mut commands, &selected, copies);
+    next_state.set(EditorState::Move);
+}
+
+/// Puts the selected splines on the clipboard as SVG markup, with the document format as the
+/// plain text alternative that [`paste`] reads back.
+fn copy_selected(
+    action_state: Res<ActionState<SelectAction>>,
+    selected_splines: SelectedSplines,
+    capture: DocumentCapture,
+    mut clipboard: NonSendMut<SystemClipboard>,
+    mut status: ResMut<Status>,
+) {
+    if !action_state.just_pressed(&SelectAction::Copy) {
+        return;
+    }
+
+    let document = SplineDocument {
+        splines: selected_splines
+            .entities()
+            .into_iter()
+            .filter_map(|e| capture.spline(e))
+            .collect(),
+        ..default()
+    };
+    if document.splines.is_empty() {
+        return;
+    }
+    let Some(clipboard) = clipboard.0.as_mut() else {
+        status.0 = "System clipboard is unavailable".into();
+        return;
+    };
+
+    let result = document
+        .to_ron()
+        .map_err(|err| err.to_string())
+        .and_then(|ron| {
+            clipboard
+                .set_html(svg::export(&document), Some(ron))
+                .map_err(|err| err.to_string())
+        });
+    match result {
+        Ok(()) => status.0 = format!("Copied {} splines", document.splines.len()),
+        Err(err) => {
+            error!("Failed to copy splines: {err}");
+            status.0 = "Failed to copy splines".into();
+        }
+    }
+}
+
+/// Adds the splines on the clipboard in place and selects them. Text that isn't one of our
+/// documents is imported as SVG, so shapes copied from other programs paste too.
+fn paste(
+    mut commands: Commands,
+    action_state: Res<ActionState<SelectAction>>,
+    mut clipboard: NonSendMut<SystemClipboard>,
+    selected: Query<Entity, With<Selected>>,
+    mut status: ResMut<Status>,
+) {
+    if !action_state.just_pressed(&SelectAction::Paste) {
+        return;
+    }
+    let Some(clipboard) = clipboard.0.as_mut() else {
+        status.0 = "System clipboard is unavailable".into();
+        return;
+    };
+
+    let document = clipboard.get_text().ok().and_then(|text| {
+        SplineDocument::from_ron(&text)
+            .ok()
+            .or_else(|| svg::import(&text).ok())
+    });
+    let pasted = document
+        .iter()
+        .flat_map(|d| d.splines.iter())
+        .map(|spline| SplineSnapshot::reserve(&mut commands, spline))
+        .collect::<Vec<_>>();
+    if pasted.is_empty() {
+        status.0 = "Nothing to paste".into();
+        return;
+    }
+
+    status.0 = format!("Pasted {} splines", pasted.len());
+    add_splines(&mut commands, &selected, pasted);
+}
+
+/// Spawns `splines` as one undoable edit and selects all of their points instead of the current
+/// selection.
+fn add_splines(
+    commands: &mut Commands,
+    selected: &Query<Entity, With<Selected>>,
+    splines: Vec<SplineSnapshot>,
+) {
+    for e in selected.iter() {
+        commands.entity(e).remove::<Selected>();
+    }
+    for handle in splines.iter().flat_map(|s| s.handles.iter()) {
+        for e in [
+            handle.entity,
+            handle.control_a.entity,
+            handle.control_b.entity,
+        ] {
+            commands.entity(e).insert(Selected);
+        }
+    }
+
+    commands.add(RecordEdit(Change::Splines(
+        splines
+            .into_iter()
+            .map(|after| SplineChange {
+                before: None,
+                after: Some(after),
+            })
+            .collect(),
+    )));
+}
@@ -0,0 +1,258 @@
+use bevy::{prelude::*, utils::HashSet};
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+
+use crate::{
+    geometry::CubicSegment,
+    spline::{ControlMode, HandleControlMode, Spline, SplineHandle},
+};
+
+use super::{
+    cursor_position,
+    history::{
+        Change, ControlPointSnapshot, HandleSnapshot, RecordEdit, Snapshots, SplineChange,
+        SplineSnapshot,
+    },
+    selection::{Pick, Picking},
+    snap::Snapping,
+    EditorAction, EditorState, SelectAction, Selected,
+};
+
+/// Inserting this close to an existing handle, in segment parameter, does nothing.
+const MIN_SPLIT_DISTANCE: f32 = 1e-3;
+
+/// Points sampled from each original segment when refitting dissolved handles.
+const REFIT_SAMPLES_PER_SEGMENT: usize = 16;
+
+/// Edits that change which handles a spline has.
+pub struct EditPlugin;
+
+impl Plugin for EditPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (insert_handle, remove_handles)
+                .run_if(in_state(EditorState::Select))
+                .after(InputManagerSystem::ManualControl),
+        );
+    }
+}
+
+/// Splits the curve under the cursor with a new handle and selects it. With snapping on, the
+/// handle goes where the snapped position projects onto the segment under the cursor.
+fn insert_handle(
+    mut commands: Commands,
+    action_state: Res<ActionState<SelectAction>>,
+    editor_action_state: Res<ActionState<EditorAction>>,
+    picking: Picking,
+    mut snapping: Snapping,
+    snapshots: Snapshots,
+    selected: Query<Entity, With<Selected>>,
+) {
+    if !action_state.just_pressed(&SelectAction::InsertHandle) {
+        return;
+    }
+    let Some(cursor) = cursor_position(&editor_action_state) else {
+        return;
+    };
+    // Pick before snapping, so a target off the curve or on another one can't change which
+    // segment gets split.
+    let Some(Pick::Curve { spline, t }) = picking.pick(cursor) else {
+        return;
+    };
+    let Some(segment) = picking
+        .curve(spline)
+        .and_then(|c| c.segments().get(t as usize).copied())
+    else {
+        return;
+    };
+    let snapped = snapping.snap(cursor, &HashSet::new(), None);
+    let t = t.floor() + segment.nearest(snapped).0;
+    let Some(before) = snapshots.capture(spline) else {
+        return;
+    };
+
+    let mut after = before.clone();
+    let Some(handle) = split_segment(&mut commands, &mut after, t) else {
+        return;
+    };
+    commands.add(RecordEdit(Change::Splines(vec![SplineChange {
+        before: Some(before),
+        after: Some(after),
+    }])));
+
+    for e in selected.iter() {
+        commands.entity(e).remove::<Selected>();
+    }
+    for e in [
+        handle.entity,
+        handle.control_a.entity,
+        handle.control_b.entity,
+    ] {
+        commands.entity(e).insert(Selected);
+    }
+}
+
+/// Inserts a handle at curve parameter `t` of `spline`, placing it and the neighbouring control
+/// points by de Casteljau subdivision so the curve keeps its exact shape.
+///
+/// The new handle and its neighbours are pinned with [`pin_automatic`].
+fn split_segment(
+    commands: &mut Commands,
+    spline: &mut SplineSnapshot,
+    t: f32,
+) -> Option<HandleSnapshot> {
+    let count = spline.handles.len();
+    let segment_count = if spline.closed {
+        count
+    } else {
+        count.saturating_sub(1)
+    };
+    if segment_count == 0 {
+        return None;
+    }
+
+    let index = (t.max(0.).floor() as usize).min(segment_count - 1);
+    let local = t - index as f32;
+    if local <= MIN_SPLIT_DISTANCE || local >= 1. - MIN_SPLIT_DISTANCE {
+        return None;
+    }
+    let next = (index + 1) % count;
+
+    let (start, end) = (spline.handles[index], spline.handles[next]);
+    let (first, second) = CubicSegment::new(
+        start.position,
+        start.control_b.position,
+        end.control_a.position,
+        end.position,
+    )
+    .split(local);
+
+    let spline_mode = spline.control_mode;
+    let pin = |mode| pin_automatic(mode, spline_mode);
+
+    let mut reserve = |position: Vec2| ControlPointSnapshot {
+        entity: commands.spawn_empty().id(),
+        position,
+    };
+    let handle = HandleSnapshot {
+        entity: reserve(first.p3).entity,
+        position: first.p3,
+        mode: pin(HandleControlMode::Inherit),
+        control_a: reserve(first.p2),
+        control_b: reserve(second.p1),
+    };
+
+    let start = &mut spline.handles[index];
+    start.control_b.position = first.p1;
+    start.mode = pin(start.mode);
+    let end = &mut spline.handles[next];
+    end.control_a.position = second.p2;
+    end.mode = pin(end.mode);
+
+    spline.handles.insert(index + 1, handle);
+    Some(handle)
+}
+
+/// Deletes the selected handles, or dissolves them by refitting the remaining control points
+/// to the removed part of the curve. Splines left with fewer than two handles are deleted
+/// entirely.
+fn remove_handles(
+    mut commands: Commands,
+    action_state: Res<ActionState<SelectAction>>,
+    splines: Query<(Entity, &Spline)>,
+    snapshots: Snapshots,
+    selected: Query<Entity, (With<SplineHandle>, With<Selected>)>,
+) {
+    let dissolve = action_state.just_pressed(&SelectAction::Dissolve);
+    if !dissolve && !action_state.just_pressed(&SelectAction::Delete) {
+        return;
+    }
+
+    let selected = selected.iter().collect::<HashSet<_>>();
+    let changes = splines
+        .iter()
+        .filter(|(_, spline)| spline.handles.iter().any(|h| selected.contains(h)))
+        .filter_map(|(entity, _)| {
+            let before = snapshots.capture(entity)?;
+            let mut after = before.clone();
+            if dissolve {
+                refit_removed(&mut after, &selected);
+            }
+            after.handles.retain(|h| !selected.contains(&h.entity));
+            Some(SplineChange {
+                before: Some(before),
+                after: (after.handles.len() >= 2).then_some(after),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if !changes.is_empty() {
+        commands.add(RecordEdit(Change::Splines(changes)));
+    }
+}
+
+/// Moves the control points on either side of each run of `removed` handles so the single
+/// segment that will replace the run follows the original curve as closely as it can.
+fn refit_removed(spline: &mut SplineSnapshot, removed: &HashSet<Entity>) {
+    let count = spline.handles.len();
+    let kept = (0..count)
+        .filter(|&i| !removed.contains(&spline.handles[i].entity))
+        .collect::<Vec<_>>();
+    let (Some(&first), Some(&last)) = (kept.first(), kept.last()) else {
+        return;
+    };
+    if kept.len() < 2 {
+        return;
+    }
+
+    let curve = spline.curve();
+    let segments = curve.segments();
+    let wrap = spline.closed.then_some((last, first));
+    let pairs = kept
+        .iter()
+        .copied()
+        .zip(kept.iter().copied().skip(1))
+        .chain(wrap);
+
+    let spline_mode = spline.control_mode;
+    for (start, end) in pairs {
+        let run = (end + count - start) % count;
+        if run < 2 {
+            continue;
+        }
+
+        let run_segments = (start..start + run)
+            .map(|i| segments[i % count])
+            .collect::<Vec<_>>();
+        let mut points = run_segments
+            .iter()
+            .flat_map(|segment| {
+                (0..REFIT_SAMPLES_PER_SEGMENT)
+                    .map(|i| segment.position(i as f32 / REFIT_SAMPLES_PER_SEGMENT as f32))
+            })
+            .collect::<Vec<_>>();
+        points.push(spline.handles[end].position);
+
+        let (fit, _) = CubicSegment::fit(
+            &points,
+            run_segments[0].tangent(0.),
+            -run_segments[run - 1].tangent(1.),
+        );
+
+        let start = &mut spline.handles[start];
+        start.control_b.position = fit.p1;
+        start.mode = pin_automatic(start.mode, spline_mode);
+        let end = &mut spline.handles[end];
+        end.control_a.position = fit.p2;
+        end.mode = pin_automatic(end.mode, spline_mode);
+    }
+}
+
+/// Automatic handles recompute their control points from their neighbours, which would undo
+/// control points placed to preserve a shape, so they're switched to aligned instead.
+fn pin_automatic(mode: HandleControlMode, spline_mode: ControlMode) -> HandleControlMode {
+    match mode.resolve(spline_mode) {
+        ControlMode::Automatic => HandleControlMode::Custom(ControlMode::Aligned),
+        _ => mode,
+    }
+}
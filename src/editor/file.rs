@@ -0,0 +1,293 @@
+use std::path::PathBuf;
+
+use bevy::{
+    prelude::*,
+    tasks::{block_on, futures_lite::future, IoTaskPool, Task},
+};
+use leafwing_input_manager::prelude::*;
+use rfd::AsyncFileDialog;
+
+use crate::{
+    document::{DocumentCapture, DocumentMetadata, SplineDocument, DOCUMENT_EXTENSION},
+    spline::{Spline, SplineControlPoint, SplineHandle},
+    svg,
+};
+
+use super::{
+    history::{Change, History, RecordEdit, SplineChange, SplineSnapshot},
+    EditorState, SelectAction, Status,
+};
+
+pub struct FilePlugin;
+
+impl Plugin for FilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DocumentPath>()
+            .init_resource::<DocumentMetadata>()
+            .init_resource::<FileDialog>()
+            .add_event::<SaveDocument>()
+            .add_event::<OpenDocument>()
+            .add_event::<ImportSvg>()
+            .add_event::<ExportSvg>()
+            .add_systems(Startup, open_initial_document)
+            .add_systems(
+                Update,
+                (
+                    request_file_action.run_if(in_state(EditorState::Select)),
+                    poll_file_dialog,
+                    save_document,
+                    open_document,
+                    import_svg,
+                    export_svg,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// File the current document was opened from or last saved to, `None` while it's untitled.
+#[derive(Resource, Default)]
+pub struct DocumentPath(pub Option<PathBuf>);
+
+/// Writes all splines to a document at the given path.
+#[derive(Event)]
+pub struct SaveDocument(pub PathBuf);
+
+/// Replaces all splines with the ones in the document at the given path.
+#[derive(Event)]
+pub struct OpenDocument(pub PathBuf);
+
+/// Adds the shapes of the SVG file at the given path to the document.
+#[derive(Event)]
+pub struct ImportSvg(pub PathBuf);
+
+/// Writes all splines to an SVG file at the given path.
+#[derive(Event)]
+pub struct ExportSvg(pub PathBuf);
+
+enum FileRequest {
+    Save(PathBuf),
+    Open(PathBuf),
+    Import(PathBuf),
+    Export(PathBuf),
+}
+
+/// Native file dialog that's currently open, if any.
+#[derive(Resource, Default)]
+struct FileDialog(Option<Task<Option<FileRequest>>>);
+
+fn open_initial_document(path: Res<DocumentPath>, mut open: EventWriter<OpenDocument>) {
+    if let Some(path) = &path.0 {
+        open.send(OpenDocument(path.clone()));
+    }
+}
+
+fn request_file_action(
+    action_state: Res<ActionState<SelectAction>>,
+    path: Res<DocumentPath>,
+    mut dialog: ResMut<FileDialog>,
+    mut save: EventWriter<SaveDocument>,
+) {
+    if dialog.0.is_some() {
+        return;
+    }
+
+    let save_as = action_state.just_pressed(&SelectAction::SaveAs);
+    if action_state.just_pressed(&SelectAction::Save) && !save_as {
+        if let Some(path) = &path.0 {
+            save.send(SaveDocument(path.clone()));
+            return;
+        }
+    }
+
+    let file_dialog = AsyncFileDialog::new();
+    let task = if action_state.just_pressed(&SelectAction::Import) {
+        IoTaskPool::get().spawn(async move {
+            let file = file_dialog.add_filter("SVG", &["svg"]).pick_file().await?;
+            Some(FileRequest::Import(file.path().to_path_buf()))
+        })
+    } else if action_state.just_pressed(&SelectAction::Export) {
+        let file_name = path
+            .0
+            .as_ref()
+            .and_then(|p| p.file_stem())
+            .map_or_else(|| "untitled".into(), |stem| stem.to_string_lossy());
+        let file_name = format!("{file_name}.svg");
+        IoTaskPool::get().spawn(async move {
+            let file = file_dialog
+                .add_filter("SVG", &["svg"])
+                .set_file_name(file_name)
+                .save_file()
+                .await?;
+            Some(FileRequest::Export(file.path().to_path_buf()))
+        })
+    } else if save_as || action_state.just_pressed(&SelectAction::Save) {
+        let file_name = path.0.as_ref().and_then(|p| p.file_name()).map_or_else(
+            || format!("untitled.{DOCUMENT_EXTENSION}"),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        IoTaskPool::get().spawn(async move {
+            let file = file_dialog
+                .add_filter("Splines", &[DOCUMENT_EXTENSION])
+                .set_file_name(file_name)
+                .save_file()
+                .await?;
+            let mut path = file.path().to_path_buf();
+            if path.extension().is_none() {
+                path.set_extension(DOCUMENT_EXTENSION);
+            }
+            Some(FileRequest::Save(path))
+        })
+    } else if action_state.just_pressed(&SelectAction::Open) {
+        IoTaskPool::get().spawn(async move {
+            let file = file_dialog
+                .add_filter("Splines", &[DOCUMENT_EXTENSION])
+                .pick_file()
+                .await?;
+            Some(FileRequest::Open(file.path().to_path_buf()))
+        })
+    } else {
+        return;
+    };
+
+    dialog.0 = Some(task);
+}
+
+fn poll_file_dialog(
+    mut dialog: ResMut<FileDialog>,
+    mut save: EventWriter<SaveDocument>,
+    mut open: EventWriter<OpenDocument>,
+    mut import: EventWriter<ImportSvg>,
+    mut export: EventWriter<ExportSvg>,
+) {
+    let Some(task) = dialog.0.as_mut() else {
+        return;
+    };
+    let Some(request) = block_on(future::poll_once(task)) else {
+        return;
+    };
+
+    dialog.0 = None;
+    match request {
+        Some(FileRequest::Save(path)) => {
+            save.send(SaveDocument(path));
+        }
+        Some(FileRequest::Open(path)) => {
+            open.send(OpenDocument(path));
+        }
+        Some(FileRequest::Import(path)) => {
+            import.send(ImportSvg(path));
+        }
+        Some(FileRequest::Export(path)) => {
+            export.send(ExportSvg(path));
+        }
+        None => {}
+    }
+}
+
+fn save_document(
+    mut events: EventReader<SaveDocument>,
+    capture: DocumentCapture,
+    metadata: Res<DocumentMetadata>,
+    mut document_path: ResMut<DocumentPath>,
+    mut status: ResMut<Status>,
+) {
+    for SaveDocument(path) in events.read() {
+        match capture.document(metadata.clone()).write(path) {
+            Ok(()) => {
+                status.0 = format!("Saved {}", path.display());
+                document_path.0 = Some(path.clone());
+            }
+            Err(err) => {
+                error!("Failed to save {}: {err}", path.display());
+                status.0 = format!("Failed to save {}", path.display());
+            }
+        }
+    }
+}
+
+/// Everything a document spawns, which opening another one replaces.
+type DocumentEntity = Or<(With<Spline>, With<SplineHandle>, With<SplineControlPoint>)>;
+
+fn open_document(
+    mut commands: Commands,
+    mut events: EventReader<OpenDocument>,
+    existing: Query<Entity, DocumentEntity>,
+    mut history: ResMut<History>,
+    mut metadata: ResMut<DocumentMetadata>,
+    mut document_path: ResMut<DocumentPath>,
+    mut status: ResMut<Status>,
+) {
+    // Only the last document opened this frame matters.
+    let Some(OpenDocument(path)) = events.read().last() else {
+        return;
+    };
+
+    let document = match SplineDocument::read(path) {
+        Ok(document) => document,
+        Err(err) => {
+            error!("Failed to open {}: {err}", path.display());
+            status.0 = format!("Failed to open {}", path.display());
+            return;
+        }
+    };
+
+    for e in existing.iter() {
+        commands.entity(e).despawn();
+    }
+    document.spawn(&mut commands);
+
+    // The history refers to entities that no longer exist.
+    history.clear();
+    *metadata = document.metadata;
+    document_path.0 = Some(path.clone());
+    status.0 = format!("Opened {}", path.display());
+}
+
+/// Imports each SVG as a single undo step.
+fn import_svg(
+    mut commands: Commands,
+    mut events: EventReader<ImportSvg>,
+    mut status: ResMut<Status>,
+) {
+    for ImportSvg(path) in events.read() {
+        let document = match svg::read(path) {
+            Ok(document) => document,
+            Err(err) => {
+                error!("Failed to import {}: {err}", path.display());
+                status.0 = format!("Failed to import {}", path.display());
+                continue;
+            }
+        };
+
+        let changes = document
+            .splines
+            .iter()
+            .map(|spline| SplineChange {
+                before: None,
+                after: Some(SplineSnapshot::reserve(&mut commands, spline)),
+            })
+            .collect::<Vec<_>>();
+        status.0 = format!("Imported {} splines from {}", changes.len(), path.display());
+        if !changes.is_empty() {
+            commands.add(RecordEdit(Change::Splines(changes)));
+        }
+    }
+}
+
+fn export_svg(
+    mut events: EventReader<ExportSvg>,
+    capture: DocumentCapture,
+    metadata: Res<DocumentMetadata>,
+    mut status: ResMut<Status>,
+) {
+    for ExportSvg(path) in events.read() {
+        match svg::write(&capture.document(metadata.clone()), path) {
+            Ok(()) => status.0 = format!("Exported {}", path.display()),
+            Err(err) => {
+                error!("Failed to export {}: {err}", path.display());
+                status.0 = format!("Failed to export {}", path.display());
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+use bevy_vello::{
+    vello::{
+        kurbo::{Affine, Circle, Line, Point, Stroke},
+        peniko::{self, Fill},
+    },
+    CoordinateSpace, VelloScene,
+};
+
+use crate::{
+    path::SplinePoint,
+    spline::{SplineControlPoint, SplineHandle},
+};
+
+use super::Selected;
+
+/// Draws handles and control points, highlighting the selected ones.
+pub struct HandleRenderPlugin;
+
+impl Plugin for HandleRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (add_point_scenes, render_handles, render_control_points),
+        );
+    }
+}
+
+/// Handles and control points that aren't drawn yet.
+type UndrawnPoint = (
+    Or<(With<SplineHandle>, With<SplineControlPoint>)>,
+    Without<VelloScene>,
+);
+
+fn add_point_scenes(mut commands: Commands, points: Query<Entity, UndrawnPoint>) {
+    for e in points.iter() {
+        commands
+            .entity(e)
+            .insert((VelloScene::default(), CoordinateSpace::default()));
+    }
+}
+
+fn render_handles(mut handles: Query<(Option<&Selected>, &mut VelloScene), With<SplineHandle>>) {
+    for (selected, mut scene) in handles.iter_mut() {
+        let selected = selected.is_some();
+        scene.reset();
+
+        let circle = Circle::new(Point::ZERO, 4.0);
+
+        scene.fill(
+            Fill::EvenOdd,
+            Affine::IDENTITY,
+            if selected {
+                peniko::Color::DARK_RED
+            } else {
+                peniko::Color::DARK_GRAY
+            },
+            None,
+            &circle,
+        );
+
+        scene.stroke(
+            &Stroke::new(1.0),
+            Affine::IDENTITY,
+            if selected {
+                peniko::Color::RED
+            } else {
+                peniko::Color::WHITE
+            },
+            None,
+            &circle,
+        );
+    }
+}
+
+fn render_control_points(
+    mut control_points: Query<(
+        &SplineControlPoint,
+        &GlobalTransform,
+        Option<&Selected>,
+        &mut VelloScene,
+    )>,
+    handles: Query<&GlobalTransform, With<SplineHandle>>,
+) {
+    for (control_point, transform, selected, mut scene) in control_points.iter_mut() {
+        let Ok(handle_transform) = handles.get(control_point.handle) else {
+            continue;
+        };
+
+        let selected = selected.is_some();
+
+        scene.reset();
+
+        scene.stroke(
+            &Stroke::new(0.5),
+            Affine::IDENTITY,
+            if selected {
+                peniko::Color::RED
+            } else {
+                peniko::Color::WHITE
+            },
+            None,
+            &Line::new(
+                Point::ZERO,
+                SplinePoint::from(
+                    (-transform.translation() + handle_transform.translation()).truncate(),
+                ),
+            ),
+        );
+
+        scene.fill(
+            Fill::EvenOdd,
+            Affine::IDENTITY,
+            if selected {
+                peniko::Color::RED
+            } else {
+                peniko::Color::WHITE
+            },
+            None,
+            &Circle::new(Point::ZERO, 2.0),
+        );
+    }
+}
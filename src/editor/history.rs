@@ -0,0 +1,745 @@
+use std::{collections::VecDeque, mem::size_of};
+
+use bevy::{
+    ecs::system::{Command, SystemParam},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    document::{DocumentCapture, HandleData, SplineData},
+    geometry::SplineCurve,
+    spline::{
+        ControlMode, HandleControlMode, Spline, SplineBundle, SplineControlPointBundle,
+        SplineHandle, SplineHandleBundle, SplineStyle,
+    },
+};
+
+use super::{EditorState, SelectAction, Selected};
+
+/// Default memory budget of the undo stack in bytes.
+const DEFAULT_BUDGET: usize = 16 * 1024 * 1024;
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<History>()
+            .init_resource::<SelectionTracker>()
+            .add_systems(Update, undo_redo.run_if(in_state(EditorState::Select)))
+            .add_systems(Last, track_selection);
+    }
+}
+
+/// The state of a spline and all of its handles and control points, including their entities
+/// so restoring it brings back the exact same entity graph.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SplineSnapshot {
+    pub entity: Entity,
+    pub name: Option<Name>,
+    pub closed: bool,
+    pub control_mode: ControlMode,
+    pub style: SplineStyle,
+    pub handles: Vec<HandleSnapshot>,
+}
+
+impl SplineSnapshot {
+    /// Lays `data` out over freshly reserved entities. Recording the snapshot as the `after` of a
+    /// [`SplineChange`] then spawns the spline as an undoable edit.
+    pub fn reserve(commands: &mut Commands, data: &SplineData) -> Self {
+        let mut reserve = || commands.spawn_empty().id();
+        let handles = data
+            .handles
+            .iter()
+            .map(|_| [reserve(), reserve(), reserve()])
+            .collect::<Vec<_>>();
+        Self::from_data(data, reserve(), handles)
+    }
+
+    /// Lays `data` out over `entity` and the handle, control point A and control point B
+    /// entities of each handle in turn.
+    fn from_data(data: &SplineData, entity: Entity, handles: Vec<[Entity; 3]>) -> Self {
+        let handles = data
+            .handles
+            .iter()
+            .zip(handles)
+            .map(|(h, [handle, control_a, control_b])| HandleSnapshot {
+                entity: handle,
+                position: h.position,
+                mode: h.mode,
+                control_a: ControlPointSnapshot {
+                    entity: control_a,
+                    position: h.control_a,
+                },
+                control_b: ControlPointSnapshot {
+                    entity: control_b,
+                    position: h.control_b,
+                },
+            })
+            .collect();
+
+        Self {
+            entity,
+            name: data.name.clone().map(Name::new),
+            closed: data.closed,
+            control_mode: data.control_mode,
+            style: data.style,
+            handles,
+        }
+    }
+
+    /// The spline as stored in documents, without its entities.
+    pub fn data(&self) -> SplineData {
+        SplineData {
+            name: self.name.as_ref().map(|n| n.as_str().to_owned()),
+            closed: self.closed,
+            control_mode: self.control_mode,
+            style: self.style,
+            handles: self
+                .handles
+                .iter()
+                .map(|h| HandleData {
+                    position: h.position,
+                    control_a: h.control_a.position,
+                    control_b: h.control_b.position,
+                    mode: h.mode,
+                })
+                .collect(),
+        }
+    }
+
+    /// Copies the snapshot onto freshly reserved entities. Handles and control points refer to
+    /// each other through the snapshot's structure rather than by id, so restoring the copy links
+    /// them to their new counterparts.
+    pub fn duplicate(&self, commands: &mut Commands) -> Self {
+        let mut reserve = || commands.spawn_empty().id();
+        let mut copy = self.clone();
+        copy.entity = reserve();
+        for handle in copy.handles.iter_mut() {
+            handle.entity = reserve();
+            handle.control_a.entity = reserve();
+            handle.control_b.entity = reserve();
+        }
+        copy
+    }
+
+    /// Opens a closed spline at `handle` by dropping the segment arriving at it, so the spline
+    /// starts at `handle` and ends at the handle that preceded it.
+    pub fn open_at(&mut self, handle: Entity) {
+        if let Some(index) = self.handles.iter().position(|h| h.entity == handle) {
+            self.handles.rotate_left(index);
+        }
+        self.closed = false;
+    }
+
+    pub fn curve(&self) -> SplineCurve {
+        self.data().curve()
+    }
+
+    fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        std::iter::once(self.entity).chain(
+            self.handles
+                .iter()
+                .flat_map(|h| [h.entity, h.control_a.entity, h.control_b.entity]),
+        )
+    }
+
+    fn size(&self) -> usize {
+        size_of::<Self>() + self.handles.len() * size_of::<HandleSnapshot>()
+    }
+
+    fn remap(&mut self, remap: &EntityRemap) {
+        remap_entity(&mut self.entity, remap);
+        for handle in &mut self.handles {
+            remap_entity(&mut handle.entity, remap);
+            remap_entity(&mut handle.control_a.entity, remap);
+            remap_entity(&mut handle.control_b.entity, remap);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HandleSnapshot {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub mode: HandleControlMode,
+    pub control_a: ControlPointSnapshot,
+    pub control_b: ControlPointSnapshot,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ControlPointSnapshot {
+    pub entity: Entity,
+    pub position: Vec2,
+}
+
+/// Captures [`SplineSnapshot`]s of the current world.
+#[derive(SystemParam)]
+pub struct Snapshots<'w, 's> {
+    capture: DocumentCapture<'w, 's>,
+    splines: Query<'w, 's, &'static Spline>,
+    handles: Query<'w, 's, &'static SplineHandle>,
+}
+
+impl<'w, 's> Snapshots<'w, 's> {
+    /// Captures the spline the same way documents do, along with the entities it's made of.
+    pub fn capture(&self, entity: Entity) -> Option<SplineSnapshot> {
+        let data = self.capture.spline(entity)?;
+        let handles = self
+            .splines
+            .get(entity)
+            .ok()?
+            .handles
+            .iter()
+            .map(|&e| {
+                let handle = self.handles.get(e).ok()?;
+                Some([e, handle.control_point_a, handle.control_point_b])
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(SplineSnapshot::from_data(&data, entity, handles))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TransformChange {
+    pub entity: Entity,
+    pub before: Vec2,
+    pub after: Vec2,
+}
+
+/// A spline going from `before` to `after`. `None` on either side means the spline doesn't
+/// exist there, so creation and deletion are changes too.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SplineChange {
+    pub before: Option<SplineSnapshot>,
+    pub after: Option<SplineSnapshot>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    Transforms(Vec<TransformChange>),
+    Splines(Vec<SplineChange>),
+    Selection {
+        before: Vec<Entity>,
+        after: Vec<Entity>,
+    },
+}
+
+impl Change {
+    fn size(&self) -> usize {
+        size_of::<Self>()
+            + match self {
+                Self::Transforms(changes) => changes.len() * size_of::<TransformChange>(),
+                Self::Splines(changes) => changes
+                    .iter()
+                    .flat_map(|c| [&c.before, &c.after])
+                    .flatten()
+                    .map(SplineSnapshot::size)
+                    .sum(),
+                Self::Selection { before, after } => {
+                    (before.len() + after.len()) * size_of::<Entity>()
+                }
+            }
+    }
+
+    /// Folds `next` into this change so both undo as a single step, handing it back if it can't
+    /// be. Moves merge with moves of the same points, keeping the original starting positions,
+    /// and moves of points this change creates are folded into the created snapshots.
+    fn merge(&mut self, next: Change) -> Result<(), Change> {
+        let Self::Transforms(transforms) = &next else {
+            return Err(next);
+        };
+
+        // Moves are only the same drag if they carry the same points along.
+        let same_points_only = matches!(self, Self::Transforms(_));
+        let mut positions = match self {
+            Self::Transforms(changes) => changes
+                .iter_mut()
+                .map(|c| (c.entity, &mut c.after))
+                .collect::<HashMap<_, _>>(),
+            Self::Splines(splines) => splines
+                .iter_mut()
+                .filter(|c| c.before.is_none())
+                .filter_map(|c| c.after.as_mut())
+                .flat_map(|s| s.handles.iter_mut())
+                .flat_map(|h| {
+                    [
+                        (h.entity, &mut h.position),
+                        (h.control_a.entity, &mut h.control_a.position),
+                        (h.control_b.entity, &mut h.control_b.position),
+                    ]
+                })
+                .collect(),
+            Self::Selection { .. } => return Err(next),
+        };
+        if (same_points_only && positions.len() != transforms.len())
+            || !transforms.iter().all(|t| positions.contains_key(&t.entity))
+        {
+            return Err(next);
+        }
+        for change in transforms {
+            if let Some(position) = positions.get_mut(&change.entity) {
+                **position = change.after;
+            }
+        }
+        Ok(())
+    }
+
+    /// Points the change at the entities that took over from reused ids.
+    fn remap(&mut self, remap: &EntityRemap) {
+        match self {
+            Self::Transforms(changes) => {
+                for change in changes {
+                    remap_entity(&mut change.entity, remap);
+                }
+            }
+            Self::Splines(changes) => {
+                for snapshot in changes
+                    .iter_mut()
+                    .flat_map(|c| [&mut c.before, &mut c.after])
+                    .flatten()
+                {
+                    snapshot.remap(remap);
+                }
+            }
+            Self::Selection { before, after } => {
+                for e in before.iter_mut().chain(after) {
+                    remap_entity(e, remap);
+                }
+            }
+        }
+    }
+
+    fn apply(&self, world: &mut World, forward: bool) -> EntityRemap {
+        let mut remap = EntityRemap::default();
+        match self {
+            Self::Transforms(changes) => {
+                for change in changes {
+                    let position = if forward { change.after } else { change.before };
+                    if let Some(mut transform) = world.get_mut::<Transform>(change.entity) {
+                        transform.translation.x = position.x;
+                        transform.translation.y = position.y;
+                    }
+                }
+            }
+            Self::Splines(changes) => {
+                for change in changes {
+                    let (from, to) = if forward {
+                        (&change.before, &change.after)
+                    } else {
+                        (&change.after, &change.before)
+                    };
+                    remap.extend(restore(world, from.as_ref(), to.as_ref()));
+                }
+            }
+            Self::Selection { before, after } => {
+                let (from, to) = if forward {
+                    (before, after)
+                } else {
+                    (after, before)
+                };
+                for &e in from {
+                    if let Some(mut entity) = world.get_entity_mut(e) {
+                        entity.remove::<Selected>();
+                    }
+                }
+                for &e in to {
+                    if let Some(mut entity) = world.get_entity_mut(e) {
+                        entity.insert(Selected);
+                    }
+                }
+            }
+        }
+        remap
+    }
+}
+
+/// Entities that couldn't come back under their old id because it had been reused, mapped to the
+/// fresh entities that took their place.
+pub(super) type EntityRemap = HashMap<Entity, Entity>;
+
+fn remap_entity(entity: &mut Entity, remap: &EntityRemap) {
+    if let Some(&fresh) = remap.get(entity) {
+        *entity = fresh;
+    }
+}
+
+/// Replaces the spline graph described by `from` with the one described by `to`, despawning
+/// entities that no longer exist and respawning ones that come back under their old ids.
+///
+/// Ids that have since been reused by other entities are swapped for fresh ones, so the spline
+/// always comes back whole. The returned remap has to be applied to anything still referring to
+/// the old ids.
+pub(super) fn restore(
+    world: &mut World,
+    from: Option<&SplineSnapshot>,
+    to: Option<&SplineSnapshot>,
+) -> EntityRemap {
+    let keep = to
+        .map(|s| s.entities().collect::<HashSet<_>>())
+        .unwrap_or_default();
+    for e in from.iter().flat_map(|s| s.entities()) {
+        if !keep.contains(&e) {
+            world.despawn(e);
+        }
+    }
+
+    let mut remap = EntityRemap::default();
+    let Some(to) = to else {
+        return remap;
+    };
+
+    // Claim every id before spawning anything, so the references between handles and control
+    // points can use the remapped ones.
+    for e in to.entities() {
+        if world.get_or_spawn(e).is_none() {
+            let fresh = world.spawn_empty().id();
+            warn!("{e:?} has been reused, restoring it as {fresh:?}");
+            remap.insert(e, fresh);
+        }
+    }
+    let mut to = to.clone();
+    to.remap(&remap);
+
+    for handle in &to.handles {
+        for control_point in [handle.control_a, handle.control_b] {
+            world
+                .entity_mut(control_point.entity)
+                .insert(SplineControlPointBundle::new(
+                    control_point.position,
+                    handle.entity,
+                ));
+        }
+
+        let mut bundle = SplineHandleBundle::new(
+            SplineHandle {
+                control_point_a: handle.control_a.entity,
+                control_point_b: handle.control_b.entity,
+            },
+            handle.position,
+        );
+        bundle.handle_control_mode = handle.mode;
+        world.entity_mut(handle.entity).insert(bundle);
+    }
+
+    let mut spline = world.entity_mut(to.entity);
+    spline.insert(SplineBundle {
+        spline: Spline {
+            handles: to.handles.iter().map(|h| h.entity).collect(),
+            closed: to.closed,
+        },
+        control_mode: to.control_mode,
+        style: to.style,
+        ..default()
+    });
+    match to.name {
+        Some(name) => {
+            spline.insert(name);
+        }
+        None => {
+            spline.remove::<Name>();
+        }
+    }
+
+    remap
+}
+
+/// Undo and redo stacks of editor changes.
+#[derive(Resource)]
+pub struct History {
+    undo: VecDeque<Change>,
+    redo: Vec<Change>,
+    /// Maximum memory the undo stack may use before the oldest steps are dropped.
+    pub budget: usize,
+    /// Whether selection changes get their own undo steps.
+    pub track_selection: bool,
+    /// Whether an interaction like a drag is going on, whose edits merge into one undo step.
+    interacting: bool,
+    /// Whether the last undo step was recorded during the current interaction.
+    merge_next: bool,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            budget: DEFAULT_BUDGET,
+            track_selection: false,
+            interacting: false,
+            merge_next: false,
+        }
+    }
+}
+
+impl History {
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.merge_next = false;
+    }
+
+    /// Starts merging edits into a single undo step, until [`Self::end_interaction`]. Does
+    /// nothing if an interaction is already going on.
+    pub fn begin_interaction(&mut self) {
+        if !self.interacting {
+            self.interacting = true;
+            self.merge_next = false;
+        }
+    }
+
+    pub fn end_interaction(&mut self) {
+        self.interacting = false;
+        self.merge_next = false;
+    }
+
+    fn push(&mut self, change: Change) {
+        self.redo.clear();
+        let merge = std::mem::replace(&mut self.merge_next, self.interacting);
+        let change = match self.undo.back_mut() {
+            Some(last) if merge => match last.merge(change) {
+                Ok(()) => return,
+                Err(change) => change,
+            },
+            _ => change,
+        };
+        self.undo.push_back(change);
+
+        let mut used = self.undo.iter().map(Change::size).sum::<usize>();
+        while used > self.budget && self.undo.len() > 1 {
+            if let Some(dropped) = self.undo.pop_front() {
+                used -= dropped.size();
+            }
+        }
+    }
+
+    /// Points every recorded step at the entities that replaced reused ids.
+    fn remap(&mut self, remap: &EntityRemap) {
+        if remap.is_empty() {
+            return;
+        }
+        for change in self.undo.iter_mut().chain(self.redo.iter_mut()) {
+            change.remap(remap);
+        }
+    }
+}
+
+/// Applies a change to the world and records it as an undo step.
+pub struct RecordEdit(pub Change);
+
+impl Command for RecordEdit {
+    fn apply(self, world: &mut World) {
+        let remap = self.0.apply(world, true);
+        let mut history = world.resource_mut::<History>();
+        history.push(self.0);
+        history.remap(&remap);
+        sync_selection_tracker(world);
+    }
+}
+
+/// Starts an interaction in order with other commands, so an edit recorded just before can
+/// already be part of it. See [`History::begin_interaction`].
+pub struct BeginInteraction;
+
+impl Command for BeginInteraction {
+    fn apply(self, world: &mut World) {
+        world.resource_mut::<History>().begin_interaction();
+    }
+}
+
+pub(super) fn begin_interaction(mut history: ResMut<History>) {
+    history.begin_interaction();
+}
+
+pub(super) fn end_interaction(mut history: ResMut<History>) {
+    history.end_interaction();
+}
+
+struct Undo;
+
+impl Command for Undo {
+    fn apply(self, world: &mut World) {
+        let Some(edit) = world.resource_mut::<History>().undo.pop_back() else {
+            return;
+        };
+        let remap = edit.apply(world, false);
+        let mut history = world.resource_mut::<History>();
+        history.redo.push(edit);
+        history.remap(&remap);
+        history.merge_next = false;
+        sync_selection_tracker(world);
+    }
+}
+
+struct Redo;
+
+impl Command for Redo {
+    fn apply(self, world: &mut World) {
+        let Some(edit) = world.resource_mut::<History>().redo.pop() else {
+            return;
+        };
+        let remap = edit.apply(world, true);
+        let mut history = world.resource_mut::<History>();
+        history.undo.push_back(edit);
+        history.remap(&remap);
+        history.merge_next = false;
+        sync_selection_tracker(world);
+    }
+}
+
+fn undo_redo(mut commands: Commands, action_state: Res<ActionState<SelectAction>>) {
+    if action_state.just_pressed(&SelectAction::Redo) {
+        commands.add(Redo);
+    } else if action_state.just_pressed(&SelectAction::Undo) {
+        commands.add(Undo);
+    }
+}
+
+/// Selection as of the last time it was recorded or restored.
+#[derive(Resource, Default)]
+struct SelectionTracker(HashSet<Entity>);
+
+/// Keeps changes made by undo and redo from being recorded as new selection steps.
+fn sync_selection_tracker(world: &mut World) {
+    let selected = world
+        .query_filtered::<Entity, With<Selected>>()
+        .iter(world)
+        .collect();
+    world.resource_mut::<SelectionTracker>().0 = selected;
+}
+
+fn track_selection(
+    mut history: ResMut<History>,
+    mut tracker: ResMut<SelectionTracker>,
+    selected: Query<Entity, With<Selected>>,
+) {
+    let current = selected.iter().collect::<HashSet<_>>();
+    if current == tracker.0 {
+        return;
+    }
+
+    if history.track_selection {
+        let change = Change::Selection {
+            before: tracker.0.iter().copied().collect(),
+            after: current.iter().copied().collect(),
+        };
+        history.push(change);
+    }
+    tracker.0 = current;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A new spline with a single handle at the origin, using entities 0 to 3.
+    fn creation() -> Change {
+        let point = |index| ControlPointSnapshot {
+            entity: Entity::from_raw(index),
+            position: Vec2::ZERO,
+        };
+        Change::Splines(vec![SplineChange {
+            before: None,
+            after: Some(SplineSnapshot {
+                entity: Entity::from_raw(0),
+                name: None,
+                closed: false,
+                control_mode: ControlMode::Free,
+                style: default(),
+                handles: vec![HandleSnapshot {
+                    entity: Entity::from_raw(1),
+                    position: Vec2::ZERO,
+                    mode: HandleControlMode::Inherit,
+                    control_a: point(2),
+                    control_b: point(3),
+                }],
+            }),
+        }])
+    }
+
+    /// Moves the points with the given entity indices from `from` to `to`.
+    fn moves(entities: &[u32], from: Vec2, to: Vec2) -> Change {
+        Change::Transforms(
+            entities
+                .iter()
+                .map(|&index| TransformChange {
+                    entity: Entity::from_raw(index),
+                    before: from,
+                    after: to,
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn drag_is_a_single_step() {
+        let (start, middle, end) = (Vec2::ZERO, Vec2::splat(5.), Vec2::splat(10.));
+        let mut history = History::default();
+        history.begin_interaction();
+        history.push(moves(&[1, 2], start, middle));
+        history.push(moves(&[2, 1], middle, end));
+        history.end_interaction();
+
+        assert_eq!(history.undo.len(), 1);
+        assert_eq!(history.undo.back(), Some(&moves(&[1, 2], start, end)));
+
+        history.push(moves(&[1, 2], end, start));
+        assert_eq!(history.undo.len(), 2);
+    }
+
+    #[test]
+    fn moves_of_other_points_during_a_drag_get_their_own_step() {
+        let mut history = History::default();
+        history.begin_interaction();
+        history.push(moves(&[1, 2], Vec2::ZERO, Vec2::ONE));
+        history.push(moves(&[1], Vec2::ONE, Vec2::ZERO));
+        history.push(moves(&[1, 2, 3], Vec2::ZERO, Vec2::ONE));
+
+        assert_eq!(history.undo.len(), 3);
+    }
+
+    #[test]
+    fn moving_what_an_interaction_created_becomes_part_of_the_creation() {
+        let mut history = History::default();
+        history.begin_interaction();
+        history.push(creation());
+        history.push(moves(&[1, 2, 3], Vec2::ZERO, Vec2::new(1., 10.)));
+        history.end_interaction();
+
+        assert_eq!(history.undo.len(), 1);
+        let Some(Change::Splines(changes)) = history.undo.back() else {
+            panic!("expected the creation to stay the last step");
+        };
+        let handle = changes[0].after.as_ref().unwrap().handles[0];
+        assert_eq!(handle.position, Vec2::new(1., 10.));
+        assert_eq!(handle.control_a.position, Vec2::new(1., 10.));
+        assert_eq!(handle.control_b.position, Vec2::new(1., 10.));
+    }
+
+    #[test]
+    fn moving_other_points_keeps_the_creation_as_is() {
+        let mut history = History::default();
+        history.begin_interaction();
+        history.push(creation());
+        history.push(moves(&[1, 7], Vec2::ZERO, Vec2::ONE));
+
+        assert_eq!(history.undo.len(), 2);
+        assert_eq!(history.undo.front(), Some(&creation()));
+    }
+
+    #[test]
+    fn only_merges_within_an_interaction() {
+        let mut history = History::default();
+        history.push(creation());
+        history.push(moves(&[1], Vec2::ZERO, Vec2::ONE));
+        history.push(moves(&[1], Vec2::ONE, Vec2::ZERO));
+        assert_eq!(history.undo.len(), 3);
+
+        // An interaction starting after an edit doesn't reach back into it.
+        history.begin_interaction();
+        history.push(moves(&[1], Vec2::ZERO, Vec2::ONE));
+        assert_eq!(history.undo.len(), 4);
+    }
+}
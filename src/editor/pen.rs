@@ -0,0 +1,309 @@
+use bevy::{
+    ecs::system::{Command, SystemState},
+    prelude::*,
+    utils::HashSet,
+};
+use bevy_vello::{
+    vello::{
+        kurbo::{Affine, BezPath, Stroke},
+        peniko,
+    },
+    VelloScene,
+};
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+
+use crate::{
+    path::SplinePoint,
+    spline::{
+        ControlMode, Spline, SplineBundle, SplineControlPoint, SplineControlPointBundle,
+        SplineHandle, SplineHandleBundle,
+    },
+};
+
+use super::{
+    cursor_position,
+    history::{restore, Change, RecordEdit, Snapshots, SplineChange},
+    selection::{Pick, Picking},
+    snap::Snapping,
+    EditorAction, EditorState, Overlay, PenAction, SelectAction, Status,
+};
+
+/// Draws new splines handle by handle.
+pub struct PenPlugin;
+
+impl Plugin for PenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Pen>()
+            .add_systems(OnEnter(EditorState::Pen), start_pen)
+            .add_systems(OnExit(EditorState::Pen), cancel_pen)
+            .add_systems(
+                Update,
+                (
+                    enter_pen.run_if(in_state(EditorState::Select)),
+                    (
+                        snap_cursor,
+                        place_handle,
+                        drag_handle,
+                        finish_pen,
+                        draw_preview,
+                    )
+                        .chain()
+                        .run_if(in_state(EditorState::Pen))
+                        .after(InputManagerSystem::ManualControl),
+                ),
+            );
+    }
+}
+
+/// The spline being drawn and the handle whose control points are being pulled out, if any.
+#[derive(Resource, Default)]
+struct Pen {
+    spline: Option<Entity>,
+    dragging: Option<Entity>,
+    /// Cursor position after snapping, updated every frame.
+    cursor: Option<Vec2>,
+}
+
+fn enter_pen(
+    action_state: Res<ActionState<SelectAction>>,
+    mut next_state: ResMut<NextState<EditorState>>,
+) {
+    if action_state.just_pressed(&SelectAction::Pen) {
+        next_state.set(EditorState::Pen);
+    }
+}
+
+/// Snaps the cursor, pulling control points out at snapped angles around their handle while
+/// dragging.
+fn snap_cursor(
+    editor_action_state: Res<ActionState<EditorAction>>,
+    mut pen: ResMut<Pen>,
+    handles: Query<(&Transform, &SplineHandle)>,
+    mut snapping: Snapping,
+) {
+    let Some(cursor) = cursor_position(&editor_action_state) else {
+        pen.cursor = None;
+        return;
+    };
+
+    let dragging = pen.dragging.and_then(|e| Some((e, handles.get(e).ok()?)));
+    let (exclude, anchor) = match dragging {
+        Some((e, (transform, handle))) => (
+            HashSet::from([e, handle.control_point_a, handle.control_point_b]),
+            Some(transform.translation.truncate()),
+        ),
+        None => (HashSet::new(), None),
+    };
+    pen.cursor = Some(snapping.snap(cursor, &exclude, anchor));
+}
+
+fn start_pen(mut status: ResMut<Status>) {
+    status.0 = "Pen: click to place handles, drag to pull out control points, \
+                Enter or click the first handle to close the spline, Esc to cancel"
+        .into();
+}
+
+/// Places a handle at the cursor, or closes the spline when clicking its first handle.
+fn place_handle(
+    mut commands: Commands,
+    action_state: Res<ActionState<PenAction>>,
+    mut pen: ResMut<Pen>,
+    mut splines: Query<&mut Spline>,
+    picking: Picking,
+    mut next_state: ResMut<NextState<EditorState>>,
+) {
+    if !action_state.just_pressed(&PenAction::Place) {
+        return;
+    }
+    let Some(cursor) = pen.cursor else {
+        return;
+    };
+
+    if let Some(mut spline) = pen.spline.and_then(|e| splines.get_mut(e).ok()) {
+        let first = spline.handles.first().copied();
+        if spline.handles.len() > 1 && picking.pick_point(cursor) == first.map(Pick::Handle) {
+            spline.closed = true;
+            finish(&mut commands, &mut pen, false);
+            next_state.set(EditorState::Select);
+            return;
+        }
+    }
+
+    let handle = commands.spawn_empty().id();
+    let control_point_a = commands
+        .spawn(SplineControlPointBundle::new(cursor, handle))
+        .id();
+    let control_point_b = commands
+        .spawn(SplineControlPointBundle::new(cursor, handle))
+        .id();
+    commands.entity(handle).insert(SplineHandleBundle::new(
+        SplineHandle {
+            control_point_a,
+            control_point_b,
+        },
+        cursor,
+    ));
+    pen.dragging = Some(handle);
+
+    match pen.spline.and_then(|e| splines.get_mut(e).ok()) {
+        Some(mut spline) => spline.handles.push(handle),
+        None => {
+            let spline = commands.spawn(SplineBundle {
+                spline: Spline {
+                    handles: vec![handle],
+                    closed: false,
+                },
+                // Control points are pulled out symmetrically, so keep them that way.
+                control_mode: ControlMode::Aligned,
+                ..default()
+            });
+            pen.spline = Some(spline.id());
+        }
+    }
+}
+
+/// Pulls the dragged handle's control points out symmetrically, the outgoing one following the
+/// cursor.
+fn drag_handle(
+    action_state: Res<ActionState<PenAction>>,
+    mut pen: ResMut<Pen>,
+    handles: Query<(&Transform, &SplineHandle)>,
+    mut control_points: Query<&mut Transform, (With<SplineControlPoint>, Without<SplineHandle>)>,
+) {
+    let Some(dragging) = pen.dragging else {
+        return;
+    };
+    if !action_state.pressed(&PenAction::Place) {
+        pen.dragging = None;
+        return;
+    }
+    let Some(cursor) = pen.cursor else {
+        return;
+    };
+    let Ok((transform, handle)) = handles.get(dragging) else {
+        return;
+    };
+    let Ok([mut control_a, mut control_b]) =
+        control_points.get_many_mut([handle.control_point_a, handle.control_point_b])
+    else {
+        return;
+    };
+
+    let position = transform.translation.truncate();
+    let mirrored = position * 2. - cursor;
+    control_a.translation.x = mirrored.x;
+    control_a.translation.y = mirrored.y;
+    control_b.translation.x = cursor.x;
+    control_b.translation.y = cursor.y;
+}
+
+/// Closes and records the spline on Finish, the same as clicking its first handle, or throws it
+/// away on Cancel.
+fn finish_pen(
+    mut commands: Commands,
+    action_state: Res<ActionState<PenAction>>,
+    mut pen: ResMut<Pen>,
+    mut splines: Query<&mut Spline>,
+    mut next_state: ResMut<NextState<EditorState>>,
+) {
+    let cancel = action_state.just_pressed(&PenAction::Cancel);
+    if !cancel && !action_state.just_pressed(&PenAction::Finish) {
+        return;
+    }
+
+    let finished = pen.spline.filter(|_| !cancel);
+    if let Some(mut spline) = finished.and_then(|e| splines.get_mut(e).ok()) {
+        spline.closed = true;
+    }
+    finish(&mut commands, &mut pen, cancel);
+    next_state.set(EditorState::Select);
+}
+
+/// Throws away a spline that's still being drawn when leaving the pen some other way.
+fn cancel_pen(mut commands: Commands, mut pen: ResMut<Pen>, mut status: ResMut<Status>) {
+    finish(&mut commands, &mut pen, true);
+    status.0.clear();
+}
+
+fn finish(commands: &mut Commands, pen: &mut Pen, cancel: bool) {
+    pen.dragging = None;
+    if let Some(spline) = pen.spline.take() {
+        commands.add(FinishSpline { spline, cancel });
+    }
+}
+
+/// Records a drawn spline as a single undoable edit, or despawns it if it was cancelled or
+/// never got a second handle.
+struct FinishSpline {
+    spline: Entity,
+    cancel: bool,
+}
+
+impl Command for FinishSpline {
+    fn apply(self, world: &mut World) {
+        let mut state = SystemState::<Snapshots>::new(world);
+        let Some(snapshot) = state.get(world).capture(self.spline) else {
+            return;
+        };
+
+        if self.cancel || snapshot.handles.len() < 2 {
+            restore(world, Some(&snapshot), None);
+        } else {
+            RecordEdit(Change::Splines(vec![SplineChange {
+                before: None,
+                after: Some(snapshot),
+            }]))
+            .apply(world);
+        }
+    }
+}
+
+/// Previews the segment the next click would add.
+fn draw_preview(
+    pen: Res<Pen>,
+    splines: Query<&Spline>,
+    handles: Query<(&Transform, &SplineHandle)>,
+    control_points: Query<&Transform, (With<SplineControlPoint>, Without<SplineHandle>)>,
+    mut overlay: Query<&mut VelloScene, With<Overlay>>,
+    picking: Picking,
+) {
+    if pen.dragging.is_some() {
+        return;
+    }
+    let Some(cursor) = pen.cursor else {
+        return;
+    };
+    let Some(&last) = pen
+        .spline
+        .and_then(|e| splines.get(e).ok())
+        .and_then(|s| s.handles.last())
+    else {
+        return;
+    };
+    let Ok((transform, handle)) = handles.get(last) else {
+        return;
+    };
+    let Ok(control_b) = control_points.get(handle.control_point_b) else {
+        return;
+    };
+    let Ok(mut scene) = overlay.get_single_mut() else {
+        return;
+    };
+
+    let mut path = BezPath::new();
+    path.move_to(SplinePoint::from(transform.translation.truncate()));
+    path.curve_to(
+        SplinePoint::from(control_b.translation.truncate()),
+        SplinePoint::from(cursor),
+        SplinePoint::from(cursor),
+    );
+
+    let width = picking.world_size(1.) as f64;
+    scene.stroke(
+        &Stroke::new(width).with_dashes(0., [4. * width, 4. * width]),
+        Affine::IDENTITY,
+        peniko::Color::WHITE,
+        None,
+        &path,
+    );
+}
@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+use bevy_vello::{
+    vello::{
+        kurbo::{Affine, BezPath, Stroke},
+        peniko,
+    },
+    VelloScene,
+};
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+
+use crate::{
+    document::{HandleData, SplineData},
+    geometry::{fit_closed_curve, fit_curve, CubicSegment},
+    path::SplinePoint,
+    spline::{ControlMode, HandleControlMode, SplineStyle},
+};
+
+use super::{
+    cursor_position,
+    history::{Change, RecordEdit, SplineChange, SplineSnapshot},
+    selection::Picking,
+    EditorAction, EditorState, Overlay, PencilAction, SelectAction, Status,
+};
+
+/// Screen distance the cursor has to move before another sample is recorded.
+const SAMPLE_SPACING: f32 = 2.;
+
+/// Strokes ending within this many screen pixels of where they started are closed.
+const CLOSE_DISTANCE: f32 = 8.;
+
+/// Range the tolerance can be adjusted in with the keyboard.
+const TOLERANCE_RANGE: (f32, f32) = (0.5, 64.);
+
+/// Sketches splines freehand, fitting the fewest handles that follow the stroke.
+pub struct PencilPlugin;
+
+impl Plugin for PencilPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PencilSettings>()
+            .init_resource::<PencilStroke>()
+            .add_systems(OnEnter(EditorState::Pencil), update_pencil_status)
+            .add_systems(OnExit(EditorState::Pencil), leave_pencil)
+            .add_systems(
+                Update,
+                (
+                    enter_pencil.run_if(in_state(EditorState::Select)),
+                    (
+                        adjust_tolerance,
+                        update_pencil_status.run_if(resource_changed::<PencilSettings>),
+                        record_stroke,
+                        finish_stroke,
+                        draw_stroke,
+                        exit_pencil,
+                    )
+                        .chain()
+                        .run_if(in_state(EditorState::Pencil))
+                        .after(InputManagerSystem::ManualControl),
+                ),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct PencilSettings {
+    /// Furthest the fitted curve may stray from the stroke, in screen pixels.
+    pub tolerance: f32,
+}
+
+impl Default for PencilSettings {
+    fn default() -> Self {
+        Self { tolerance: 4. }
+    }
+}
+
+/// Cursor samples of the stroke being drawn, in world space.
+#[derive(Resource, Default)]
+struct PencilStroke(Option<Vec<Vec2>>);
+
+fn enter_pencil(
+    action_state: Res<ActionState<SelectAction>>,
+    mut next_state: ResMut<NextState<EditorState>>,
+) {
+    if action_state.just_pressed(&SelectAction::Pencil) {
+        next_state.set(EditorState::Pencil);
+    }
+}
+
+fn update_pencil_status(settings: Res<PencilSettings>, mut status: ResMut<Status>) {
+    status.0 = format!(
+        "Pencil: drag to draw, tolerance {} px ([ and ] to adjust), Esc to leave",
+        settings.tolerance
+    );
+}
+
+fn adjust_tolerance(
+    action_state: Res<ActionState<PencilAction>>,
+    mut settings: ResMut<PencilSettings>,
+) {
+    let factor = if action_state.just_pressed(&PencilAction::IncreaseTolerance) {
+        2.
+    } else if action_state.just_pressed(&PencilAction::DecreaseTolerance) {
+        0.5
+    } else {
+        return;
+    };
+    settings.tolerance = (settings.tolerance * factor).clamp(TOLERANCE_RANGE.0, TOLERANCE_RANGE.1);
+}
+
+fn record_stroke(
+    action_state: Res<ActionState<PencilAction>>,
+    editor_action_state: Res<ActionState<EditorAction>>,
+    mut stroke: ResMut<PencilStroke>,
+    picking: Picking,
+) {
+    if !action_state.pressed(&PencilAction::Draw) {
+        return;
+    }
+    let Some(cursor) = cursor_position(&editor_action_state) else {
+        return;
+    };
+
+    let samples = stroke.0.get_or_insert_with(Vec::new);
+    if samples
+        .last()
+        .is_none_or(|last| last.distance(cursor) >= picking.world_size(SAMPLE_SPACING))
+    {
+        samples.push(cursor);
+    }
+}
+
+/// Fits the stroke once the button is released and adds it as a single undoable edit.
+fn finish_stroke(
+    mut commands: Commands,
+    action_state: Res<ActionState<PencilAction>>,
+    settings: Res<PencilSettings>,
+    mut stroke: ResMut<PencilStroke>,
+    picking: Picking,
+) {
+    if action_state.pressed(&PencilAction::Draw) {
+        return;
+    }
+    let Some(samples) = stroke.0.take() else {
+        return;
+    };
+
+    let tolerance = picking.world_size(settings.tolerance);
+    let closed = samples.len() > 2
+        && samples[0].distance(samples[samples.len() - 1]) <= picking.world_size(CLOSE_DISTANCE);
+    let segments = if closed {
+        fit_closed_curve(&samples, tolerance)
+    } else {
+        fit_curve(&samples, tolerance)
+    };
+    let Some(spline) = spline_data(&segments, closed) else {
+        return;
+    };
+
+    let after = SplineSnapshot::reserve(&mut commands, &spline);
+    commands.add(RecordEdit(Change::Splines(vec![SplineChange {
+        before: None,
+        after: Some(after),
+    }])));
+}
+
+/// Turns a chain of fitted segments into handles. The ends of open curves get their unused
+/// control point mirrored, so every handle holds up under [`ControlMode::Aligned`].
+fn spline_data(segments: &[CubicSegment], closed: bool) -> Option<SplineData> {
+    let (first, last) = (segments.first()?, segments.last()?);
+    let handle = |position: Vec2, control_a: Vec2, control_b: Vec2| HandleData {
+        position,
+        control_a,
+        control_b,
+        mode: HandleControlMode::Inherit,
+    };
+
+    let mut handles = segments
+        .iter()
+        .zip(segments.iter().cycle().skip(segments.len() - 1))
+        .map(|(segment, previous)| handle(segment.p0, previous.p2, segment.p1))
+        .collect::<Vec<_>>();
+    if !closed {
+        handles[0].control_a = first.p0 * 2. - first.p1;
+        handles.push(handle(last.p3, last.p2, last.p3 * 2. - last.p2));
+    }
+
+    Some(SplineData {
+        name: None,
+        closed,
+        control_mode: ControlMode::Aligned,
+        style: SplineStyle::default(),
+        handles,
+    })
+}
+
+fn draw_stroke(
+    stroke: Res<PencilStroke>,
+    mut overlay: Query<&mut VelloScene, With<Overlay>>,
+    picking: Picking,
+) {
+    let Some(samples) = stroke.0.as_ref() else {
+        return;
+    };
+    let Ok(mut scene) = overlay.get_single_mut() else {
+        return;
+    };
+
+    let mut points = samples.iter().copied().map(SplinePoint::from);
+    let mut path = BezPath::new();
+    if let Some(first) = points.next() {
+        path.move_to(first);
+    }
+    for point in points {
+        path.line_to(point);
+    }
+
+    scene.stroke(
+        &Stroke::new(picking.world_size(1.5) as f64),
+        Affine::IDENTITY,
+        peniko::Color::WHITE,
+        None,
+        &path,
+    );
+}
+
+fn exit_pencil(
+    action_state: Res<ActionState<PencilAction>>,
+    mut next_state: ResMut<NextState<EditorState>>,
+) {
+    if action_state.just_pressed(&PencilAction::Exit) {
+        next_state.set(EditorState::Select);
+    }
+}
+
+fn leave_pencil(mut stroke: ResMut<PencilStroke>, mut status: ResMut<Status>) {
+    stroke.0 = None;
+    status.0.clear();
+}
@@ -0,0 +1,196 @@
+use bevy::{ecs::system::Command, prelude::*};
+use bevy_rapier2d::prelude::*;
+use bevy_vello::{
+    vello::{
+        kurbo::{Affine, Circle, Point},
+        peniko::{self, Fill},
+    },
+    CoordinateSpace, VelloScene,
+};
+use leafwing_input_manager::prelude::*;
+
+use crate::{physics::SplineCollider, spline::Spline};
+
+use super::{
+    cursor_position,
+    history::{restore, Snapshots, SplineSnapshot},
+    EditorAction, EditorState, PlayAction, SelectAction, Status,
+};
+
+/// Radius of the balls dropped while playing, in world units.
+const BODY_RADIUS: f32 = 12.;
+
+/// Lets the document be played with rapier: splines get colliders and clicking drops test bodies
+/// onto them. Stopping throws the bodies away and puts every spline back as it was.
+///
+/// The app has to add `RapierPhysicsPlugin`, this only pauses and resumes it. Without it, play
+/// mode can't be entered.
+pub struct PlayPlugin;
+
+impl Plugin for PlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlaySnapshot>()
+            .add_systems(Startup, pause_physics)
+            .add_systems(OnEnter(EditorState::Play), start_play)
+            .add_systems(OnExit(EditorState::Play), stop_play)
+            .add_systems(
+                Update,
+                (
+                    toggle_play
+                        .run_if(in_state(EditorState::Select).or_else(in_state(EditorState::Play))),
+                    drop_body.run_if(in_state(EditorState::Play)),
+                ),
+            )
+            .add_systems(PostUpdate, (add_body_scenes, render_bodies).chain());
+    }
+}
+
+/// Splines as they were when play started.
+#[derive(Resource, Default)]
+struct PlaySnapshot(Vec<SplineSnapshot>);
+
+/// A dynamic body dropped while playing, despawned when play stops.
+#[derive(Component)]
+struct TestBody {
+    radius: f32,
+}
+
+/// Marks splines that only have a [`SplineCollider`] for the duration of play.
+#[derive(Component)]
+struct PlayCollider;
+
+fn pause_physics(config: Option<ResMut<RapierConfiguration>>) {
+    if let Some(mut config) = config {
+        config.physics_pipeline_active = false;
+    }
+}
+
+fn toggle_play(
+    select_action_state: Res<ActionState<SelectAction>>,
+    play_action_state: Res<ActionState<PlayAction>>,
+    state: Res<State<EditorState>>,
+    mut next_state: ResMut<NextState<EditorState>>,
+    config: Option<Res<RapierConfiguration>>,
+    mut status: ResMut<Status>,
+) {
+    match state.get() {
+        EditorState::Select if select_action_state.just_pressed(&SelectAction::Play) => {
+            if config.is_some() {
+                next_state.set(EditorState::Play);
+            } else {
+                status.0 = "Playing needs RapierPhysicsPlugin".into();
+            }
+        }
+        EditorState::Play if play_action_state.just_pressed(&PlayAction::Stop) => {
+            next_state.set(EditorState::Select);
+        }
+        _ => {}
+    }
+}
+
+fn start_play(
+    mut commands: Commands,
+    splines: Query<(Entity, Has<SplineCollider>), With<Spline>>,
+    snapshots: Snapshots,
+    mut snapshot: ResMut<PlaySnapshot>,
+    mut config: ResMut<RapierConfiguration>,
+    mut status: ResMut<Status>,
+) {
+    snapshot.0 = splines
+        .iter()
+        .filter_map(|(e, _)| snapshots.capture(e))
+        .collect();
+
+    for (e, has_collider) in splines.iter() {
+        if !has_collider {
+            commands
+                .entity(e)
+                .insert((SplineCollider::default(), PlayCollider));
+        }
+    }
+
+    config.physics_pipeline_active = true;
+    status.0 = "Playing: click to drop a ball, Esc or F5 to stop".into();
+}
+
+fn stop_play(
+    mut commands: Commands,
+    bodies: Query<Entity, With<TestBody>>,
+    play_colliders: Query<Entity, With<PlayCollider>>,
+    mut snapshot: ResMut<PlaySnapshot>,
+    mut config: ResMut<RapierConfiguration>,
+    mut status: ResMut<Status>,
+) {
+    config.physics_pipeline_active = false;
+
+    for e in bodies.iter() {
+        commands.entity(e).despawn();
+    }
+    for e in play_colliders.iter() {
+        commands
+            .entity(e)
+            .remove::<(SplineCollider, PlayCollider)>();
+    }
+
+    commands.add(RestoreSnapshot(std::mem::take(&mut snapshot.0)));
+    status.0.clear();
+}
+
+/// Puts the splines back exactly as they were captured, in case anything touched them while
+/// playing.
+struct RestoreSnapshot(Vec<SplineSnapshot>);
+
+impl Command for RestoreSnapshot {
+    fn apply(self, world: &mut World) {
+        for snapshot in &self.0 {
+            restore(world, None, Some(snapshot));
+        }
+    }
+}
+
+fn drop_body(
+    mut commands: Commands,
+    action_state: Res<ActionState<PlayAction>>,
+    editor_action_state: Res<ActionState<EditorAction>>,
+) {
+    if !action_state.just_pressed(&PlayAction::DropBody) {
+        return;
+    }
+    let Some(position) = cursor_position(&editor_action_state) else {
+        return;
+    };
+
+    commands.spawn((
+        TestBody {
+            radius: BODY_RADIUS,
+        },
+        RigidBody::Dynamic,
+        Collider::ball(BODY_RADIUS),
+        Restitution::coefficient(0.3),
+        SpatialBundle::from_transform(Transform::from_translation(position.extend(20.))),
+    ));
+}
+
+fn add_body_scenes(
+    mut commands: Commands,
+    bodies: Query<Entity, (With<TestBody>, Without<VelloScene>)>,
+) {
+    for e in bodies.iter() {
+        commands
+            .entity(e)
+            .insert((VelloScene::default(), CoordinateSpace::default()));
+    }
+}
+
+fn render_bodies(mut bodies: Query<(&TestBody, &mut VelloScene)>) {
+    for (body, mut scene) in bodies.iter_mut() {
+        scene.reset();
+        scene.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            peniko::Color::ORANGE,
+            None,
+            &Circle::new(Point::ZERO, body.radius as f64),
+        );
+    }
+}
@@ -0,0 +1,424 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_vello::{
+    vello::{
+        kurbo::{self, Affine, BezPath, Shape, Stroke},
+        peniko::{self, Fill},
+    },
+    VelloScene,
+};
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+
+use crate::{
+    camera::MainCamera,
+    geometry::{polygon_contains, SplineCurve},
+    path::SplinePoint,
+    spline::{Spline, SplineControlPoint, SplineHandle, SplineSampler},
+};
+
+use super::{cursor_position, EditorAction, EditorState, Overlay, SelectAction, Selected};
+
+/// Pick radius in screen pixels, converted to world units using the camera zoom.
+const PICK_RADIUS: f32 = 8.;
+
+/// Distance in screen pixels the cursor has to travel before a click turns into a drag.
+const DRAG_THRESHOLD: f32 = 4.;
+
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectionFilter>()
+            .init_resource::<RegionSelect>()
+            .add_systems(
+                Update,
+                (
+                    start_selection,
+                    update_region,
+                    finish_region,
+                    draw_region,
+                    cycle_selection_filter,
+                    deselect_all,
+                )
+                    .chain()
+                    .run_if(in_state(EditorState::Select))
+                    .after(InputManagerSystem::ManualControl),
+            )
+            .add_systems(OnExit(EditorState::Select), cancel_region);
+    }
+}
+
+/// Which kinds of points box and lasso selection pick up.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SelectionFilter {
+    #[default]
+    All,
+    Handles,
+    ControlPoints,
+}
+
+impl SelectionFilter {
+    fn next(self) -> Self {
+        match self {
+            Self::All => Self::Handles,
+            Self::Handles => Self::ControlPoints,
+            Self::ControlPoints => Self::All,
+        }
+    }
+
+    fn includes_handles(self) -> bool {
+        matches!(self, Self::All | Self::Handles)
+    }
+
+    fn includes_control_points(self) -> bool {
+        matches!(self, Self::All | Self::ControlPoints)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Pick {
+    Handle(Entity),
+    ControlPoint(Entity),
+    Curve { spline: Entity, t: f32 },
+}
+
+/// Hit testing against handles, control points and spline curves.
+#[derive(SystemParam)]
+pub struct Picking<'w, 's> {
+    camera: Query<'w, 's, &'static OrthographicProjection, With<MainCamera>>,
+    handles: Query<'w, 's, (Entity, &'static GlobalTransform), With<SplineHandle>>,
+    control_points: Query<'w, 's, (Entity, &'static GlobalTransform), With<SplineControlPoint>>,
+    splines: Query<'w, 's, (Entity, &'static SplineSampler), With<Spline>>,
+}
+
+impl<'w, 's> Picking<'w, 's> {
+    /// Converts a length in screen pixels to world units at the current zoom.
+    pub fn world_size(&self, pixels: f32) -> f32 {
+        pixels * self.camera.get_single().map_or(1., |p| p.scale)
+    }
+
+    /// The closest handle or control point under `cursor`, falling back to the closest curve.
+    pub fn pick(&self, cursor: Vec2) -> Option<Pick> {
+        self.pick_point(cursor).or_else(|| self.pick_curve(cursor))
+    }
+
+    pub fn pick_point(&self, cursor: Vec2) -> Option<Pick> {
+        let radius = self.world_size(PICK_RADIUS);
+
+        self.handles
+            .iter()
+            .map(|(e, t)| (Pick::Handle(e), t))
+            .chain(
+                self.control_points
+                    .iter()
+                    .map(|(e, t)| (Pick::ControlPoint(e), t)),
+            )
+            .map(|(pick, t)| (pick, t.translation().truncate().distance(cursor)))
+            .filter(|&(_, distance)| distance <= radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(pick, _)| pick)
+    }
+
+    /// The curve [`Pick::Curve`] parameters refer to.
+    pub fn curve(&self, spline: Entity) -> Option<&SplineCurve> {
+        self.splines
+            .get(spline)
+            .ok()
+            .map(|(_, sampler)| sampler.curve())
+    }
+
+    pub fn pick_curve(&self, cursor: Vec2) -> Option<Pick> {
+        let radius = self.world_size(PICK_RADIUS);
+
+        self.splines
+            .iter()
+            .filter_map(|(spline, sampler)| {
+                let (t, distance) = sampler.curve().nearest(cursor)?;
+                (distance <= radius).then_some((Pick::Curve { spline, t }, distance))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(pick, _)| pick)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum RegionShape {
+    Box,
+    Lasso,
+}
+
+impl RegionShape {
+    fn action(self) -> SelectAction {
+        match self {
+            Self::Box => SelectAction::Select,
+            Self::Lasso => SelectAction::Lasso,
+        }
+    }
+}
+
+struct Region {
+    shape: RegionShape,
+    /// Corners for a box, the traced outline for a lasso.
+    points: Vec<Vec2>,
+    dragging: bool,
+    /// What was under the cursor when the region started, used if it ends up being a click.
+    click: Option<Pick>,
+}
+
+#[derive(Resource, Default)]
+struct RegionSelect(Option<Region>);
+
+fn start_selection(
+    mut commands: Commands,
+    action_state: Res<ActionState<SelectAction>>,
+    editor_action_state: Res<ActionState<EditorAction>>,
+    mut region: ResMut<RegionSelect>,
+    picking: Picking,
+    handles: Query<(&SplineHandle, Has<Selected>)>,
+    control_points: Query<Has<Selected>, With<SplineControlPoint>>,
+) {
+    let Some(cursor) = cursor_position(&editor_action_state) else {
+        return;
+    };
+
+    if action_state.just_pressed(&SelectAction::Select) {
+        match picking.pick(cursor) {
+            Some(Pick::Handle(entity)) => {
+                let Ok((handle, is_selected)) = handles.get(entity) else {
+                    return;
+                };
+                // Handles drag their control points along, so they're (de)selected together.
+                for e in [entity, handle.control_point_a, handle.control_point_b] {
+                    set_selected(&mut commands, e, !is_selected);
+                }
+            }
+            Some(Pick::ControlPoint(entity)) => {
+                let Ok(is_selected) = control_points.get(entity) else {
+                    return;
+                };
+                set_selected(&mut commands, entity, !is_selected);
+            }
+            click => {
+                region.0 = Some(Region {
+                    shape: RegionShape::Box,
+                    points: vec![cursor, cursor],
+                    dragging: false,
+                    click,
+                });
+            }
+        }
+    } else if action_state.just_pressed(&SelectAction::Lasso) && region.0.is_none() {
+        region.0 = Some(Region {
+            shape: RegionShape::Lasso,
+            points: vec![cursor],
+            dragging: false,
+            click: None,
+        });
+    }
+}
+
+fn update_region(
+    editor_action_state: Res<ActionState<EditorAction>>,
+    mut region: ResMut<RegionSelect>,
+    picking: Picking,
+) {
+    let Some(region) = region.0.as_mut() else {
+        return;
+    };
+    let Some(cursor) = cursor_position(&editor_action_state) else {
+        return;
+    };
+
+    let threshold = picking.world_size(DRAG_THRESHOLD);
+    if region.points[0].distance(cursor) > threshold {
+        region.dragging = true;
+    }
+
+    match region.shape {
+        RegionShape::Box => region.points[1] = cursor,
+        RegionShape::Lasso => {
+            if region
+                .points
+                .last()
+                .is_none_or(|last| last.distance(cursor) > threshold)
+            {
+                region.points.push(cursor);
+            }
+        }
+    }
+}
+
+/// Everything a finished region or click can select from, along with the current selection.
+#[derive(SystemParam)]
+struct Selectable<'w, 's> {
+    handles: Query<'w, 's, (Entity, &'static GlobalTransform, &'static SplineHandle)>,
+    control_points: Query<'w, 's, (Entity, &'static GlobalTransform), With<SplineControlPoint>>,
+    splines: Query<'w, 's, &'static Spline>,
+    selected: Query<'w, 's, Entity, With<Selected>>,
+}
+
+fn finish_region(
+    mut commands: Commands,
+    action_state: Res<ActionState<SelectAction>>,
+    mut region: ResMut<RegionSelect>,
+    filter: Res<SelectionFilter>,
+    selectable: Selectable,
+) {
+    let Selectable {
+        handles,
+        control_points,
+        splines,
+        selected,
+    } = selectable;
+    let Some(shape) = region.0.as_ref().map(|r| r.shape) else {
+        return;
+    };
+    if action_state.pressed(&shape.action()) {
+        return;
+    }
+    let Some(region) = region.0.take() else {
+        return;
+    };
+
+    if !region.dragging {
+        match region.click {
+            Some(Pick::Curve { spline, .. }) => {
+                let Ok(spline) = splines.get(spline) else {
+                    return;
+                };
+                let points = spline
+                    .handles
+                    .iter()
+                    .filter_map(|&e| handles.get(e).ok())
+                    .flat_map(|(e, _, handle)| [e, handle.control_point_a, handle.control_point_b])
+                    .collect::<Vec<_>>();
+                let all_selected = points.iter().all(|&e| selected.contains(e));
+                for e in points {
+                    set_selected(&mut commands, e, !all_selected);
+                }
+            }
+            _ if region.shape == RegionShape::Box => {
+                for e in selected.iter() {
+                    commands.entity(e).remove::<Selected>();
+                }
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    let contains = |point: Vec2| match region.shape {
+        RegionShape::Box => Rect::from_corners(region.points[0], region.points[1]).contains(point),
+        RegionShape::Lasso => polygon_contains(&region.points, point),
+    };
+
+    let handle_points = handles
+        .iter()
+        .filter(|_| filter.includes_handles())
+        .map(|(e, t, _)| (e, t));
+    let control_point_points = control_points
+        .iter()
+        .filter(|_| filter.includes_control_points());
+    let inside = handle_points
+        .chain(control_point_points)
+        .filter(|(_, t)| contains(t.translation().truncate()))
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+
+    let add = action_state.pressed(&SelectAction::AddToSelection);
+    let subtract = action_state.pressed(&SelectAction::SubtractFromSelection);
+
+    if !add && !subtract {
+        for e in selected.iter().filter(|e| !inside.contains(e)) {
+            commands.entity(e).remove::<Selected>();
+        }
+    }
+    for e in inside {
+        set_selected(&mut commands, e, !subtract);
+    }
+}
+
+fn draw_region(
+    region: Res<RegionSelect>,
+    mut overlay: Query<&mut VelloScene, With<Overlay>>,
+    picking: Picking,
+) {
+    let Some(region) = region.0.as_ref().filter(|r| r.dragging) else {
+        return;
+    };
+    let Ok(mut scene) = overlay.get_single_mut() else {
+        return;
+    };
+
+    let mut path = BezPath::new();
+    match region.shape {
+        RegionShape::Box => {
+            let rect = kurbo::Rect::from_points(
+                SplinePoint::from(region.points[0]),
+                SplinePoint::from(region.points[1]),
+            );
+            path.extend(rect.path_elements(0.1));
+        }
+        RegionShape::Lasso => {
+            let mut points = region.points.iter().copied().map(SplinePoint::from);
+            if let Some(first) = points.next() {
+                path.move_to(first);
+            }
+            for point in points {
+                path.line_to(point);
+            }
+            path.close_path();
+        }
+    }
+
+    let width = picking.world_size(1.) as f64;
+    scene.fill(
+        Fill::EvenOdd,
+        Affine::IDENTITY,
+        peniko::Color::rgba8(255, 255, 255, 16),
+        None,
+        &path,
+    );
+    scene.stroke(
+        &Stroke::new(width).with_dashes(0., [4. * width, 4. * width]),
+        Affine::IDENTITY,
+        peniko::Color::WHITE,
+        None,
+        &path,
+    );
+}
+
+fn cancel_region(mut region: ResMut<RegionSelect>) {
+    region.0 = None;
+}
+
+fn cycle_selection_filter(
+    action_state: Res<ActionState<SelectAction>>,
+    mut filter: ResMut<SelectionFilter>,
+) {
+    if action_state.just_pressed(&SelectAction::CycleSelectionFilter) {
+        *filter = filter.next();
+        info!("Selection filter: {:?}", *filter);
+    }
+}
+
+fn deselect_all(
+    mut commands: Commands,
+    action_state: Res<ActionState<SelectAction>>,
+    selected: Query<Entity, With<Selected>>,
+) {
+    if !action_state.just_pressed(&SelectAction::Deselect) {
+        return;
+    }
+
+    for e in selected.iter() {
+        commands.entity(e).remove::<Selected>();
+    }
+}
+
+fn set_selected(commands: &mut Commands, entity: Entity, selected: bool) {
+    if selected {
+        commands.entity(entity).insert(Selected);
+    } else {
+        commands.entity(entity).remove::<Selected>();
+    }
+}
@@ -0,0 +1,355 @@
+use std::f32::consts::PI;
+
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashSet, window::PrimaryWindow};
+use bevy_vello::{
+    vello::{
+        kurbo::{Affine, BezPath, Circle, Line, Stroke},
+        peniko,
+    },
+    VelloScene,
+};
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+
+use crate::{
+    camera::grid_spacing,
+    geometry::CubicSegment,
+    path::SplinePoint,
+    spline::{Spline, SplineControlPoint, SplineHandle, SplineSampler},
+};
+
+use super::{selection::Picking, EditorAction, EditorState, Overlay, SelectAction, Status};
+
+/// Increment tangent directions snap to.
+const ANGLE_SNAP: f32 = PI / 12.;
+
+/// Size of the snap target markers in screen pixels.
+const MARKER_SIZE: f32 = 6.;
+
+/// Snaps moved points, inserted handles and pen clicks to the grid, existing points and curve
+/// features.
+pub struct SnapPlugin;
+
+impl Plugin for SnapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SnapSettings>()
+            .init_resource::<SnapIndicator>()
+            .add_systems(
+                Update,
+                toggle_snapping
+                    .run_if(in_state(EditorState::Select))
+                    .after(InputManagerSystem::ManualControl),
+            )
+            .add_systems(PostUpdate, draw_snap_target);
+    }
+}
+
+/// What points snap to. Holding the invert modifier flips `enabled` for as long as it's held.
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+pub struct SnapSettings {
+    pub enabled: bool,
+    /// The finest grid subdivision currently drawn.
+    pub grid: bool,
+    /// Handles and control points.
+    pub points: bool,
+    /// Points halfway along each segment by length.
+    pub midpoints: bool,
+    /// Points where a segment turns around horizontally or vertically.
+    pub extrema: bool,
+    /// Points where segments cross.
+    pub intersections: bool,
+    /// Tangent directions in 15° steps around their handle.
+    pub angle: bool,
+    /// How close the cursor has to get to a point or curve feature, in screen pixels.
+    pub radius: f32,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            grid: true,
+            points: true,
+            midpoints: true,
+            extrema: true,
+            intersections: true,
+            angle: true,
+            radius: 10.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SnapKind {
+    Grid,
+    Point,
+    Midpoint,
+    Extremum,
+    Intersection,
+    Angle { anchor: Vec2 },
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SnapTarget {
+    pub position: Vec2,
+    pub kind: SnapKind,
+}
+
+/// The target snapped to this frame, highlighted on the overlay and then cleared.
+#[derive(Resource, Default)]
+struct SnapIndicator(Option<SnapTarget>);
+
+/// Handles and control points.
+type PointFilter = Or<(With<SplineHandle>, With<SplineControlPoint>)>;
+
+/// Finds what a point snaps to under the current [`SnapSettings`].
+#[derive(SystemParam)]
+pub struct Snapping<'w, 's> {
+    settings: Res<'w, SnapSettings>,
+    action_state: Res<'w, ActionState<EditorAction>>,
+    indicator: ResMut<'w, SnapIndicator>,
+    picking: Picking<'w, 's>,
+    window: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    points: Query<'w, 's, (Entity, &'static GlobalTransform), PointFilter>,
+    splines: Query<'w, 's, (&'static Spline, &'static SplineSampler)>,
+    handles: Query<'w, 's, &'static SplineHandle>,
+}
+
+impl<'w, 's> Snapping<'w, 's> {
+    /// Snaps `point`, ignoring the points in `exclude` and the segments they shape. Tangents
+    /// snap their angle around `anchor` when given.
+    pub fn snap(&mut self, point: Vec2, exclude: &HashSet<Entity>, anchor: Option<Vec2>) -> Vec2 {
+        let target = self.find(point, exclude, anchor);
+        self.indicator.0 = target;
+        target.map_or(point, |t| t.position)
+    }
+
+    fn find(
+        &self,
+        point: Vec2,
+        exclude: &HashSet<Entity>,
+        anchor: Option<Vec2>,
+    ) -> Option<SnapTarget> {
+        let settings = *self.settings;
+        if settings.enabled == self.action_state.pressed(&EditorAction::InvertSnap) {
+            return None;
+        }
+
+        let radius = self.picking.world_size(settings.radius);
+        let mut nearest: Option<(SnapTarget, f32)> = None;
+        let mut consider = |position: Vec2, kind: SnapKind| {
+            let distance = position.distance(point);
+            if distance <= radius && nearest.is_none_or(|(_, d)| distance < d) {
+                nearest = Some((SnapTarget { position, kind }, distance));
+            }
+        };
+
+        if settings.points {
+            for (entity, transform) in self.points.iter() {
+                if !exclude.contains(&entity) {
+                    consider(transform.translation().truncate(), SnapKind::Point);
+                }
+            }
+        }
+
+        let segments = self.segments_near(point, radius, exclude);
+        for segment in segments.iter() {
+            if settings.midpoints {
+                consider(segment.position(arc_midpoint(segment)), SnapKind::Midpoint);
+            }
+            if settings.extrema {
+                for t in segment.extrema() {
+                    consider(segment.position(t), SnapKind::Extremum);
+                }
+            }
+        }
+        if settings.intersections {
+            let tolerance = self.picking.world_size(0.25);
+            for (i, a) in segments.iter().enumerate() {
+                for b in segments[i + 1..].iter() {
+                    for (t, _) in a.intersections(b, tolerance) {
+                        let position = a.position(t);
+                        // Consecutive segments meet at their shared handle, which isn't a crossing.
+                        let at_end = |s: &CubicSegment| {
+                            s.p0.distance(position) <= tolerance * 2.
+                                || s.p3.distance(position) <= tolerance * 2.
+                        };
+                        if !(at_end(a) && at_end(b)) {
+                            consider(position, SnapKind::Intersection);
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some((target, _)) = nearest {
+            return Some(target);
+        }
+
+        if let Some(anchor) = anchor.filter(|_| settings.angle) {
+            let offset = point - anchor;
+            if offset.length_squared() > f32::EPSILON {
+                let angle = (offset.y.atan2(offset.x) / ANGLE_SNAP).round() * ANGLE_SNAP;
+                return Some(SnapTarget {
+                    position: anchor + Vec2::from_angle(angle) * offset.length(),
+                    kind: SnapKind::Angle { anchor },
+                });
+            }
+        }
+
+        if settings.grid {
+            let scale_factor = self.window.get_single().map_or(1., |w| w.scale_factor());
+            let spacing = grid_spacing(self.picking.world_size(1.) / scale_factor);
+            return Some(SnapTarget {
+                position: (point / spacing).round() * spacing,
+                kind: SnapKind::Grid,
+            });
+        }
+
+        None
+    }
+
+    /// Segments whose hull comes within `radius` of `point`, skipping those with an end or
+    /// control point in `exclude` since they move along with it.
+    fn segments_near(
+        &self,
+        point: Vec2,
+        radius: f32,
+        exclude: &HashSet<Entity>,
+    ) -> Vec<CubicSegment> {
+        let excluded = |handle: Entity, control_point: fn(&SplineHandle) -> Entity| {
+            exclude.contains(&handle)
+                || self
+                    .handles
+                    .get(handle)
+                    .is_ok_and(|h| exclude.contains(&control_point(h)))
+        };
+
+        let mut segments = Vec::new();
+        for (spline, sampler) in self.splines.iter() {
+            let handles = &spline.handles;
+            for (i, segment) in sampler.curve().segments().iter().enumerate() {
+                let hull = segment.hull();
+                if !Rect::from_corners(hull.min - radius, hull.max + radius).contains(point) {
+                    continue;
+                }
+                let (Some(&start), Some(&end)) =
+                    (handles.get(i), handles.get((i + 1) % handles.len()))
+                else {
+                    continue;
+                };
+                if excluded(start, |h| h.control_point_b) || excluded(end, |h| h.control_point_a) {
+                    continue;
+                }
+                segments.push(*segment);
+            }
+        }
+        segments
+    }
+}
+
+/// Parameter halfway along `segment` by length.
+fn arc_midpoint(segment: &CubicSegment) -> f32 {
+    let half = segment.arc_length(0., 1.) / 2.;
+    let (mut low, mut high) = (0., 1.);
+    for _ in 0..16 {
+        let mid = (low + high) / 2.;
+        if segment.arc_length(0., mid) < half {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) / 2.
+}
+
+fn toggle_snapping(
+    action_state: Res<ActionState<SelectAction>>,
+    mut settings: ResMut<SnapSettings>,
+    mut status: ResMut<Status>,
+) {
+    let (flag, label) = if action_state.just_pressed(&SelectAction::ToggleSnap) {
+        (&mut settings.enabled, "Snapping")
+    } else if action_state.just_pressed(&SelectAction::ToggleGridSnap) {
+        (&mut settings.grid, "Grid snapping")
+    } else if action_state.just_pressed(&SelectAction::TogglePointSnap) {
+        (&mut settings.points, "Point snapping")
+    } else if action_state.just_pressed(&SelectAction::ToggleMidpointSnap) {
+        (&mut settings.midpoints, "Midpoint snapping")
+    } else if action_state.just_pressed(&SelectAction::ToggleExtremumSnap) {
+        (&mut settings.extrema, "Extremum snapping")
+    } else if action_state.just_pressed(&SelectAction::ToggleIntersectionSnap) {
+        (&mut settings.intersections, "Intersection snapping")
+    } else if action_state.just_pressed(&SelectAction::ToggleAngleSnap) {
+        (&mut settings.angle, "Angle snapping")
+    } else {
+        return;
+    };
+
+    *flag = !*flag;
+    status.0 = format!("{label} {}", if *flag { "on" } else { "off" });
+}
+
+/// Marks the snap target with a shape depending on its kind.
+fn draw_snap_target(
+    mut indicator: ResMut<SnapIndicator>,
+    mut overlay: Query<&mut VelloScene, With<Overlay>>,
+    picking: Picking,
+) {
+    let Some(target) = indicator.0.take() else {
+        return;
+    };
+    let Ok(mut scene) = overlay.get_single_mut() else {
+        return;
+    };
+
+    let color = peniko::Color::rgb8(255, 170, 0);
+    let width = picking.world_size(1.5) as f64;
+    let size = picking.world_size(MARKER_SIZE);
+    let corner = |x: f32, y: f32| SplinePoint::from(target.position + Vec2::new(x, y) * size);
+    let mut path = BezPath::new();
+    let mut polygon = |corners: &[(f32, f32)]| {
+        for (i, &(x, y)) in corners.iter().enumerate() {
+            if i == 0 {
+                path.move_to(corner(x, y));
+            } else {
+                path.line_to(corner(x, y));
+            }
+        }
+        path.close_path();
+    };
+
+    match target.kind {
+        SnapKind::Grid => {
+            polygon(&[(-1., 0.), (1., 0.)]);
+            polygon(&[(0., -1.), (0., 1.)]);
+        }
+        SnapKind::Point => polygon(&[(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)]),
+        SnapKind::Midpoint => polygon(&[(0., 1.), (-1., -0.7), (1., -0.7)]),
+        SnapKind::Extremum => polygon(&[(0., 1.), (1., 0.), (0., -1.), (-1., 0.)]),
+        SnapKind::Intersection => {
+            polygon(&[(-1., -1.), (1., 1.)]);
+            polygon(&[(-1., 1.), (1., -1.)]);
+        }
+        SnapKind::Angle { anchor } => {
+            scene.stroke(
+                &Stroke::new(width).with_dashes(0., [4. * width, 4. * width]),
+                Affine::IDENTITY,
+                color,
+                None,
+                &Line::new(
+                    SplinePoint::from(anchor),
+                    SplinePoint::from(target.position),
+                ),
+            );
+            scene.stroke(
+                &Stroke::new(width),
+                Affine::IDENTITY,
+                color,
+                None,
+                &Circle::new(SplinePoint::from(target.position), (size / 2.) as f64),
+            );
+        }
+    }
+
+    scene.stroke(&Stroke::new(width), Affine::IDENTITY, color, None, &path);
+}
@@ -0,0 +1,721 @@
+use std::f32::consts::PI;
+
+use bevy::{
+    ecs::system::SystemParam,
+    input::{keyboard::KeyboardInput, ButtonState},
+    prelude::*,
+    utils::HashSet,
+};
+use bevy_vello::{
+    vello::{
+        kurbo::{Affine, Circle, Line, Stroke},
+        peniko,
+    },
+    VelloScene,
+};
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+
+use crate::{
+    path::SplinePoint,
+    spline::{SplineControlPoint, SplineHandle},
+};
+
+use super::{
+    cursor_position,
+    history::{begin_interaction, end_interaction, Change, RecordEdit, TransformChange},
+    selection::Picking,
+    snap::Snapping,
+    update_edit_cursor_start, EditCursorStart, EditorAction, EditorState, Overlay, SelectAction,
+    Selected, Status, TransformAction,
+};
+
+/// Angle increment used while snapping rotations.
+const ROTATION_SNAP: f32 = PI / 12.;
+
+/// Factor increment used while snapping scales.
+const SCALE_SNAP: f32 = 0.1;
+
+pub struct ModalTransformPlugin;
+
+impl Plugin for ModalTransformPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AxisConstraint>()
+            .init_resource::<PivotPoint>()
+            .init_resource::<TransformPivot>()
+            .init_resource::<Rotation>()
+            .init_resource::<Cursor2d>()
+            .init_resource::<NumericInput>()
+            .init_resource::<SnapReference>()
+            .add_systems(
+                OnEnter(EditorState::Move),
+                (start_transform, begin_interaction),
+            )
+            .add_systems(
+                OnEnter(EditorState::Rotate),
+                (start_transform, begin_interaction),
+            )
+            .add_systems(
+                OnEnter(EditorState::Scale),
+                (start_transform, begin_interaction),
+            )
+            .add_systems(OnExit(EditorState::Move), (end_transform, end_interaction))
+            .add_systems(
+                OnExit(EditorState::Rotate),
+                (end_transform, end_interaction),
+            )
+            .add_systems(OnExit(EditorState::Scale), (end_transform, end_interaction))
+            .add_systems(
+                Update,
+                (
+                    (place_cursor, cycle_pivot).run_if(in_state(EditorState::Select)),
+                    (
+                        read_numeric_input,
+                        toggle_axis_constraint.run_if(
+                            in_state(EditorState::Move).or_else(in_state(EditorState::Scale)),
+                        ),
+                        move_selected.run_if(in_state(EditorState::Move)),
+                        rotate_selected.run_if(in_state(EditorState::Rotate)),
+                        scale_selected.run_if(in_state(EditorState::Scale)),
+                        finish_transform,
+                        (draw_axis_constraint, draw_pivot),
+                    )
+                        .chain()
+                        .run_if(transforming),
+                    draw_cursor,
+                )
+                    .after(update_edit_cursor_start)
+                    .after(InputManagerSystem::ManualControl),
+            );
+    }
+}
+
+fn transforming(state: Res<State<EditorState>>) -> bool {
+    matches!(
+        state.get(),
+        EditorState::Move | EditorState::Rotate | EditorState::Scale
+    )
+}
+
+/// Position of an entity when the current modal transform started, restored on revert.
+#[derive(Component)]
+pub struct Transforming {
+    start_pos: Vec2,
+}
+
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+enum AxisConstraint {
+    #[default]
+    None,
+    X,
+    Y,
+}
+
+impl AxisConstraint {
+    fn label(self) -> &'static str {
+        match self {
+            Self::None => "",
+            Self::X => "  along X",
+            Self::Y => "  along Y",
+        }
+    }
+
+    fn apply(self, delta: Vec2) -> Vec2 {
+        match self {
+            Self::None => delta,
+            Self::X => Vec2::new(delta.x, 0.),
+            Self::Y => Vec2::new(0., delta.y),
+        }
+    }
+
+    fn toggle(self, axis: Self) -> Self {
+        if self == axis {
+            Self::None
+        } else {
+            axis
+        }
+    }
+}
+
+/// What rotations and scales happen around.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum PivotPoint {
+    #[default]
+    MedianPoint,
+    BoundingBoxCenter,
+    Cursor,
+}
+
+impl PivotPoint {
+    fn next(self) -> Self {
+        match self {
+            Self::MedianPoint => Self::BoundingBoxCenter,
+            Self::BoundingBoxCenter => Self::Cursor,
+            Self::Cursor => Self::MedianPoint,
+        }
+    }
+}
+
+/// World-space position of the 2D cursor, placed under the mouse with Shift + C.
+#[derive(Resource, Clone, Copy, Default)]
+pub struct Cursor2d(pub Vec2);
+
+/// Pivot of the current modal transform, resolved when it starts.
+#[derive(Resource, Default)]
+struct TransformPivot(Vec2);
+
+/// Rotation accumulated since the modal rotation started, tracked incrementally so it can go
+/// past a half turn.
+#[derive(Resource, Default)]
+struct Rotation {
+    angle: f32,
+    last_direction: Option<Vec2>,
+}
+
+/// The moved point closest to where the move started, which is what snaps, carrying the rest
+/// of the selection along. Control points moving without their handle snap their angle around
+/// it.
+#[derive(Resource, Default)]
+struct SnapReference(Option<SnapPoint>);
+
+struct SnapPoint {
+    start_pos: Vec2,
+    anchor: Option<Vec2>,
+}
+
+/// Snaps a move through its [`SnapReference`].
+#[derive(SystemParam)]
+struct MoveSnapping<'w, 's> {
+    reference: Res<'w, SnapReference>,
+    snapping: Snapping<'w, 's>,
+}
+
+impl<'w, 's> MoveSnapping<'w, 's> {
+    /// Adjusts `delta` so the reference lands on a snap target, ignoring the points in `moving`.
+    fn snap(&mut self, delta: Vec2, moving: &HashSet<Entity>) -> Vec2 {
+        let Some(reference) = self.reference.0.as_ref() else {
+            return delta;
+        };
+        let snapped = self
+            .snapping
+            .snap(reference.start_pos + delta, moving, reference.anchor);
+        snapped - reference.start_pos
+    }
+}
+
+/// Exact value typed while transforming, overriding the mouse.
+///
+/// Moves take a distance along the constrained axis or an `x,y` pair, rotations an angle in
+/// degrees and scales a factor.
+#[derive(Resource, Default)]
+struct NumericInput(String);
+
+impl NumericInput {
+    fn values(&self) -> Option<Vec<f32>> {
+        if self.0.chars().all(|c| !c.is_ascii_digit()) {
+            return None;
+        }
+
+        Some(
+            self.0
+                .split(',')
+                .map(|v| v.parse().unwrap_or_default())
+                .collect(),
+        )
+    }
+
+    fn value(&self) -> Option<f32> {
+        self.values().and_then(|v| v.first().copied())
+    }
+
+    fn delta(&self, constraint: AxisConstraint) -> Option<Vec2> {
+        let values = self.values()?;
+        Some(match (values.as_slice(), constraint) {
+            ([x, y, ..], AxisConstraint::None) => Vec2::new(*x, *y),
+            ([v, ..], AxisConstraint::Y) => Vec2::new(0., *v),
+            ([v, ..], _) => Vec2::new(*v, 0.),
+            ([], _) => Vec2::ZERO,
+        })
+    }
+
+    /// Suffix for the status bar showing what's been typed so far.
+    fn label(&self) -> String {
+        if self.0.is_empty() {
+            String::new()
+        } else {
+            format!("  [{}_]", self.0)
+        }
+    }
+}
+
+/// The selection a modal transform starts from.
+#[derive(SystemParam)]
+struct TransformSelection<'w, 's> {
+    selected: Query<'w, 's, (Entity, Option<&'static SplineHandle>), With<Selected>>,
+    selected_handles: Query<'w, 's, (), (With<SplineHandle>, With<Selected>)>,
+    selected_control_points: Query<'w, 's, &'static SplineControlPoint, With<Selected>>,
+    control_points: Query<'w, 's, &'static SplineControlPoint>,
+    transforms: Query<'w, 's, &'static Transform>,
+}
+
+/// State a modal transform resets when it starts.
+#[derive(SystemParam)]
+struct TransformState<'w> {
+    pivot: ResMut<'w, TransformPivot>,
+    rotation: ResMut<'w, Rotation>,
+    numeric: ResMut<'w, NumericInput>,
+    snap_reference: ResMut<'w, SnapReference>,
+}
+
+/// What drives a running modal transform: the cursor and where it started, the transform keys
+/// and any typed value.
+#[derive(SystemParam)]
+struct TransformInput<'w> {
+    editor_action_state: Res<'w, ActionState<EditorAction>>,
+    action_state: Res<'w, ActionState<TransformAction>>,
+    cursor_start: Res<'w, EditCursorStart>,
+    constraint: Res<'w, AxisConstraint>,
+    numeric: Res<'w, NumericInput>,
+}
+
+impl<'w> TransformInput<'w> {
+    fn cursor(&self) -> Option<Vec2> {
+        cursor_position(&self.editor_action_state)
+    }
+
+    fn snap(&self) -> bool {
+        self.action_state.pressed(&TransformAction::Snap)
+    }
+}
+
+/// Snapshots everything the transform will touch: the selection, plus the control points of
+/// selected handles since handles carry their control points along.
+fn start_transform(
+    mut commands: Commands,
+    selection: TransformSelection,
+    action_state: Res<ActionState<EditorAction>>,
+    pivot_point: Res<PivotPoint>,
+    cursor: Res<Cursor2d>,
+    state: TransformState,
+) {
+    let TransformSelection {
+        selected,
+        selected_handles,
+        selected_control_points,
+        control_points,
+        transforms,
+    } = selection;
+    let TransformState {
+        mut pivot,
+        mut rotation,
+        mut numeric,
+        mut snap_reference,
+    } = state;
+
+    let targets = selected
+        .iter()
+        .flat_map(|(e, handle)| {
+            let control_points = handle.map(|h| [h.control_point_a, h.control_point_b]);
+            std::iter::once(e).chain(control_points.into_iter().flatten())
+        })
+        .collect::<HashSet<_>>();
+
+    for &e in targets.iter() {
+        if let Ok(transform) = transforms.get(e) {
+            commands.entity(e).insert(Transforming {
+                start_pos: transform.translation.truncate(),
+            });
+        }
+    }
+
+    // Control points of selected handles follow their handle, so only the handle counts
+    // towards the pivot. That way a lone handle pivots around itself.
+    let pivot_points = selected
+        .iter()
+        .filter(|(e, _)| {
+            selected_control_points
+                .get(*e)
+                .map_or(true, |c| !selected_handles.contains(c.handle))
+        })
+        .filter_map(|(e, _)| transforms.get(e).ok())
+        .map(|t| t.translation.truncate())
+        .collect::<Vec<_>>();
+
+    pivot.0 = match *pivot_point {
+        PivotPoint::Cursor => cursor.0,
+        _ if pivot_points.is_empty() => cursor.0,
+        PivotPoint::MedianPoint => pivot_points.iter().sum::<Vec2>() / pivot_points.len() as f32,
+        PivotPoint::BoundingBoxCenter => {
+            let min = pivot_points
+                .iter()
+                .copied()
+                .reduce(Vec2::min)
+                .unwrap_or_default();
+            let max = pivot_points
+                .iter()
+                .copied()
+                .reduce(Vec2::max)
+                .unwrap_or_default();
+            (min + max) / 2.
+        }
+    };
+
+    // `EditCursorStart` only catches up after this runs, so read the cursor directly.
+    snap_reference.0 = cursor_position(&action_state).and_then(|cursor_pos| {
+        let (e, start_pos) = targets
+            .iter()
+            .filter_map(|&e| Some((e, transforms.get(e).ok()?.translation.truncate())))
+            .min_by(|a, b| {
+                a.1.distance(cursor_pos)
+                    .total_cmp(&b.1.distance(cursor_pos))
+            })?;
+        let anchor = control_points
+            .get(e)
+            .ok()
+            .filter(|c| !targets.contains(&c.handle))
+            .and_then(|c| transforms.get(c.handle).ok())
+            .map(|t| t.translation.truncate());
+        Some(SnapPoint { start_pos, anchor })
+    });
+
+    *rotation = Rotation::default();
+    numeric.0.clear();
+}
+
+fn end_transform(
+    mut commands: Commands,
+    transforming: Query<Entity, With<Transforming>>,
+    mut constraint: ResMut<AxisConstraint>,
+    mut numeric: ResMut<NumericInput>,
+    mut status: ResMut<Status>,
+) {
+    for e in transforming.iter() {
+        commands.entity(e).remove::<Transforming>();
+    }
+    *constraint = AxisConstraint::None;
+    numeric.0.clear();
+    status.0.clear();
+}
+
+fn place_cursor(
+    action_state: Res<ActionState<SelectAction>>,
+    editor_action_state: Res<ActionState<EditorAction>>,
+    mut cursor: ResMut<Cursor2d>,
+) {
+    if action_state.just_pressed(&SelectAction::PlaceCursor) {
+        if let Some(position) = cursor_position(&editor_action_state) {
+            cursor.0 = position;
+        }
+    }
+}
+
+fn cycle_pivot(action_state: Res<ActionState<SelectAction>>, mut pivot: ResMut<PivotPoint>) {
+    if action_state.just_pressed(&SelectAction::CyclePivot) {
+        *pivot = pivot.next();
+        info!("Pivot point: {:?}", *pivot);
+    }
+}
+
+fn read_numeric_input(mut events: EventReader<KeyboardInput>, mut numeric: ResMut<NumericInput>) {
+    for event in events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+
+        let digit = match event.key_code {
+            KeyCode::Digit0 | KeyCode::Numpad0 => Some('0'),
+            KeyCode::Digit1 | KeyCode::Numpad1 => Some('1'),
+            KeyCode::Digit2 | KeyCode::Numpad2 => Some('2'),
+            KeyCode::Digit3 | KeyCode::Numpad3 => Some('3'),
+            KeyCode::Digit4 | KeyCode::Numpad4 => Some('4'),
+            KeyCode::Digit5 | KeyCode::Numpad5 => Some('5'),
+            KeyCode::Digit6 | KeyCode::Numpad6 => Some('6'),
+            KeyCode::Digit7 | KeyCode::Numpad7 => Some('7'),
+            KeyCode::Digit8 | KeyCode::Numpad8 => Some('8'),
+            KeyCode::Digit9 | KeyCode::Numpad9 => Some('9'),
+            KeyCode::Period | KeyCode::NumpadDecimal => Some('.'),
+            KeyCode::Comma => Some(','),
+            _ => None,
+        };
+
+        // Start of the value currently being typed.
+        let start = numeric.0.rfind(',').map_or(0, |i| i + 1);
+        match event.key_code {
+            // A second decimal point wouldn't parse, so it's ignored rather than zeroing the value.
+            _ if digit == Some('.') && numeric.0[start..].contains('.') => {}
+            _ if digit.is_some() => numeric.0.extend(digit),
+            KeyCode::Minus | KeyCode::NumpadSubtract => {
+                // Negates the value currently being typed.
+                if numeric.0[start..].starts_with('-') {
+                    numeric.0.remove(start);
+                } else {
+                    numeric.0.insert(start, '-');
+                }
+            }
+            KeyCode::Backspace => {
+                numeric.0.pop();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn toggle_axis_constraint(
+    action_state: Res<ActionState<TransformAction>>,
+    mut constraint: ResMut<AxisConstraint>,
+) {
+    if action_state.just_pressed(&TransformAction::ConstrainToX) {
+        *constraint = constraint.toggle(AxisConstraint::X);
+    }
+    if action_state.just_pressed(&TransformAction::ConstrainToY) {
+        *constraint = constraint.toggle(AxisConstraint::Y);
+    }
+}
+
+fn move_selected(
+    mut transforming: Query<(Entity, &mut Transform, &Transforming)>,
+    input: TransformInput,
+    mut snapping: MoveSnapping,
+    mut status: ResMut<Status>,
+) {
+    let constraint = *input.constraint;
+    let delta = input.numeric.delta(constraint).unwrap_or_else(|| {
+        let cursor_start = input.cursor_start.0.unwrap_or_default();
+        let cursor_pos = input.cursor().unwrap_or_default();
+        let moving = transforming.iter().map(|(e, ..)| e).collect::<HashSet<_>>();
+        let delta = snapping.snap(constraint.apply(cursor_pos - cursor_start), &moving);
+        constraint.apply(delta)
+    });
+
+    status.0 = format!(
+        "Move  X: {:.2}  Y: {:.2}{}{}",
+        delta.x,
+        delta.y,
+        constraint.label(),
+        input.numeric.label()
+    );
+    for (_, mut transform, transforming) in transforming.iter_mut() {
+        let new_pos = transforming.start_pos + delta;
+        transform.translation.x = new_pos.x;
+        transform.translation.y = new_pos.y;
+    }
+}
+
+fn rotate_selected(
+    mut transforming: Query<(&mut Transform, &Transforming)>,
+    input: TransformInput,
+    pivot: Res<TransformPivot>,
+    mut rotation: ResMut<Rotation>,
+    mut status: ResMut<Status>,
+) {
+    if let Some(cursor_pos) = input.cursor() {
+        let direction = cursor_pos - pivot.0;
+        if direction.length_squared() > f32::EPSILON {
+            let last_direction = rotation
+                .last_direction
+                .or_else(|| input.cursor_start.0.map(|start| start - pivot.0))
+                .filter(|d| d.length_squared() > f32::EPSILON)
+                .unwrap_or(direction);
+            rotation.angle += last_direction.angle_between(direction);
+            rotation.last_direction = Some(direction);
+        }
+    }
+
+    let angle = if let Some(degrees) = input.numeric.value() {
+        degrees.to_radians()
+    } else if input.snap() {
+        (rotation.angle / ROTATION_SNAP).round() * ROTATION_SNAP
+    } else {
+        rotation.angle
+    };
+
+    status.0 = format!(
+        "Rotate  {:.1}°{}",
+        angle.to_degrees(),
+        input.numeric.label()
+    );
+
+    let rotate = Vec2::from_angle(angle);
+    for (mut transform, transforming) in transforming.iter_mut() {
+        let new_pos = pivot.0 + rotate.rotate(transforming.start_pos - pivot.0);
+        transform.translation.x = new_pos.x;
+        transform.translation.y = new_pos.y;
+    }
+}
+
+/// Scales by how far the cursor is from the pivot compared to where it started. Dragging
+/// through the pivot flips the sign and mirrors the selection.
+fn scale_selected(
+    mut transforming: Query<(&mut Transform, &Transforming)>,
+    input: TransformInput,
+    pivot: Res<TransformPivot>,
+    mut status: ResMut<Status>,
+) {
+    let factor = input.numeric.value().unwrap_or_else(|| {
+        let Some(cursor_pos) = input.cursor() else {
+            return 1.;
+        };
+        let start = input.cursor_start.0.unwrap_or(cursor_pos) - pivot.0;
+        let current = cursor_pos - pivot.0;
+        if start.length_squared() <= f32::EPSILON {
+            return 1.;
+        }
+
+        let mut factor = current.length() / start.length();
+        if current.dot(start) < 0. {
+            factor = -factor;
+        }
+        if input.snap() {
+            factor = (factor / SCALE_SNAP).round() * SCALE_SNAP;
+        }
+        factor
+    });
+
+    status.0 = format!(
+        "Scale  {:.3}{}{}",
+        factor,
+        input.constraint.label(),
+        input.numeric.label()
+    );
+
+    let scale = match *input.constraint {
+        AxisConstraint::None => Vec2::splat(factor),
+        AxisConstraint::X => Vec2::new(factor, 1.),
+        AxisConstraint::Y => Vec2::new(1., factor),
+    };
+    for (mut transform, transforming) in transforming.iter_mut() {
+        let new_pos = pivot.0 + (transforming.start_pos - pivot.0) * scale;
+        transform.translation.x = new_pos.x;
+        transform.translation.y = new_pos.y;
+    }
+}
+
+/// Reverts or commits the transform. Committed transforms become a single undo step.
+fn finish_transform(
+    mut commands: Commands,
+    action_state: Res<ActionState<TransformAction>>,
+    mut transforming: Query<(Entity, &mut Transform, &Transforming)>,
+    mut next_edit_state: ResMut<NextState<EditorState>>,
+) {
+    if action_state.just_pressed(&TransformAction::Revert) {
+        for (_, mut transform, transforming) in transforming.iter_mut() {
+            transform.translation.x = transforming.start_pos.x;
+            transform.translation.y = transforming.start_pos.y;
+        }
+        next_edit_state.set(EditorState::Select);
+    } else if action_state.just_pressed(&TransformAction::Commit) {
+        let changes = transforming
+            .iter()
+            .map(|(entity, transform, transforming)| TransformChange {
+                entity,
+                before: transforming.start_pos,
+                after: transform.translation.truncate(),
+            })
+            .filter(|c| c.before != c.after)
+            .collect::<Vec<_>>();
+        if !changes.is_empty() {
+            commands.add(RecordEdit(Change::Transforms(changes)));
+        }
+        next_edit_state.set(EditorState::Select);
+    }
+}
+
+fn draw_axis_constraint(
+    state: Res<State<EditorState>>,
+    constraint: Res<AxisConstraint>,
+    pivot: Res<TransformPivot>,
+    transforming: Query<&Transforming>,
+    mut overlay: Query<&mut VelloScene, With<Overlay>>,
+    picking: Picking,
+) {
+    let (direction, color) = match *constraint {
+        AxisConstraint::None => return,
+        AxisConstraint::X => (Vec2::X, peniko::Color::rgb8(230, 80, 80)),
+        AxisConstraint::Y => (Vec2::Y, peniko::Color::rgb8(130, 200, 80)),
+    };
+    let Ok(mut scene) = overlay.get_single_mut() else {
+        return;
+    };
+
+    let count = transforming.iter().len();
+    if count == 0 {
+        return;
+    }
+    // Moves are constrained relative to where the selection was, scales to the pivot.
+    let origin = match state.get() {
+        EditorState::Scale => pivot.0,
+        _ => transforming.iter().map(|m| m.start_pos).sum::<Vec2>() / count as f32,
+    };
+    let extent = direction * picking.world_size(10_000.);
+
+    scene.stroke(
+        &Stroke::new(picking.world_size(1.) as f64),
+        Affine::IDENTITY,
+        color,
+        None,
+        &Line::new(
+            SplinePoint::from(origin - extent),
+            SplinePoint::from(origin + extent),
+        ),
+    );
+}
+
+/// Draws a dashed line from the pivot to the cursor while rotating or scaling.
+fn draw_pivot(
+    state: Res<State<EditorState>>,
+    pivot: Res<TransformPivot>,
+    action_state: Res<ActionState<EditorAction>>,
+    mut overlay: Query<&mut VelloScene, With<Overlay>>,
+    picking: Picking,
+) {
+    if *state.get() == EditorState::Move {
+        return;
+    }
+    let Some(cursor_pos) = cursor_position(&action_state) else {
+        return;
+    };
+    let Ok(mut scene) = overlay.get_single_mut() else {
+        return;
+    };
+
+    let width = picking.world_size(1.) as f64;
+    scene.stroke(
+        &Stroke::new(width).with_dashes(0., [4. * width, 4. * width]),
+        Affine::IDENTITY,
+        peniko::Color::WHITE,
+        None,
+        &Line::new(SplinePoint::from(pivot.0), SplinePoint::from(cursor_pos)),
+    );
+}
+
+fn draw_cursor(
+    cursor: Res<Cursor2d>,
+    mut overlay: Query<&mut VelloScene, With<Overlay>>,
+    picking: Picking,
+) {
+    let Ok(mut scene) = overlay.get_single_mut() else {
+        return;
+    };
+
+    let width = picking.world_size(1.) as f64;
+    let radius = picking.world_size(6.);
+    let center = SplinePoint::from(cursor.0);
+    scene.stroke(
+        &Stroke::new(width).with_dashes(0., [2. * width, 2. * width]),
+        Affine::IDENTITY,
+        peniko::Color::rgb8(230, 80, 80),
+        None,
+        &Circle::new(center, radius as f64),
+    );
+    for axis in [Vec2::X, Vec2::Y] {
+        scene.stroke(
+            &Stroke::new(width),
+            Affine::IDENTITY,
+            peniko::Color::WHITE,
+            None,
+            &Line::new(
+                SplinePoint::from(cursor.0 - axis * radius * 1.5),
+                SplinePoint::from(cursor.0 + axis * radius * 1.5),
+            ),
+        );
+    }
+}
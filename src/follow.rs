@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+
+use crate::spline::SplineSampler;
+
+/// Moves entities with a [`FollowSpline`] along their spline and sends [`SplineEndReached`].
+pub struct FollowSplinePlugin;
+
+impl Plugin for FollowSplinePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SplineEndReached>()
+            .add_systems(Update, follow_splines);
+    }
+}
+
+/// Moves an entity along a [`Spline`](crate::spline::Spline) at a constant speed in world units,
+/// regardless of how its handles are spaced.
+///
+/// Sets the entity's translation directly, so it should not have a parent that moves it.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct FollowSpline {
+    pub spline: Entity,
+    /// World units per second, negative to travel from the end towards the start.
+    pub speed: f32,
+    /// Current distance along the spline, advanced by `speed` every frame.
+    pub offset: f32,
+    pub loop_mode: LoopMode,
+    /// Rotates the entity to face its direction of travel.
+    pub orient_to_tangent: bool,
+}
+
+impl FollowSpline {
+    pub fn new(spline: Entity, speed: f32) -> Self {
+        Self {
+            spline,
+            speed,
+            offset: 0.,
+            loop_mode: LoopMode::default(),
+            orient_to_tangent: false,
+        }
+    }
+}
+
+/// What a [`FollowSpline`] does when it runs off either end of its spline.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum LoopMode {
+    /// Wraps around to the other end.
+    #[default]
+    Loop,
+    /// Bounces back, flipping the sign of `speed`.
+    PingPong,
+    /// Stops at the end it reached.
+    Clamp,
+}
+
+/// Which end of a spline a follower reached.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SplineEnd {
+    Start,
+    End,
+}
+
+/// Sent when a [`FollowSpline`] entity reaches either end of its spline. Clamped followers send
+/// it once on arrival, looping and ping-ponging ones every time they pass an end.
+#[derive(Event, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SplineEndReached {
+    pub entity: Entity,
+    pub spline: Entity,
+    pub end: SplineEnd,
+}
+
+fn follow_splines(
+    mut followers: Query<(Entity, &mut FollowSpline, &mut Transform)>,
+    samplers: Query<&SplineSampler>,
+    time: Res<Time>,
+    mut ends: EventWriter<SplineEndReached>,
+) {
+    for (entity, mut follow, mut transform) in followers.iter_mut() {
+        let Ok(sampler) = samplers.get(follow.spline) else {
+            continue;
+        };
+        let length = sampler.length();
+        if length <= 0. {
+            continue;
+        }
+
+        let previous = follow.offset;
+        let mut offset = previous + follow.speed * time.delta_seconds();
+        let end = if offset >= length {
+            Some(SplineEnd::End)
+        } else if offset < 0. {
+            Some(SplineEnd::Start)
+        } else {
+            None
+        };
+
+        let mut reached = end;
+        match follow.loop_mode {
+            LoopMode::Loop => offset = offset.rem_euclid(length),
+            LoopMode::PingPong => {
+                if end.is_some() {
+                    // Reflect the overshoot back onto the spline.
+                    offset = length - (offset.rem_euclid(2. * length) - length).abs();
+                    follow.speed = -follow.speed;
+                }
+            }
+            LoopMode::Clamp => {
+                offset = offset.clamp(0., length);
+                if offset == previous {
+                    reached = None;
+                }
+            }
+        }
+
+        if let Some(end) = reached {
+            ends.send(SplineEndReached {
+                entity,
+                spline: follow.spline,
+                end,
+            });
+        }
+        follow.offset = offset;
+
+        let t = sampler.parameter_at_distance(offset);
+        let curve = sampler.curve();
+        let position = curve.position(t);
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+
+        if follow.orient_to_tangent {
+            let tangent = curve.tangent(t) * follow.speed.signum();
+            if tangent != Vec2::ZERO {
+                transform.rotation = Quat::from_rotation_z(tangent.y.atan2(tangent.x));
+            }
+        }
+    }
+}
@@ -0,0 +1,1031 @@
+use bevy::prelude::*;
+
+/// A single cubic Bézier segment in world space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CubicSegment {
+    pub p0: Vec2,
+    pub p1: Vec2,
+    pub p2: Vec2,
+    pub p3: Vec2,
+}
+
+impl CubicSegment {
+    pub fn new(p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2) -> Self {
+        Self { p0, p1, p2, p3 }
+    }
+
+    pub fn position(&self, t: f32) -> Vec2 {
+        let mt = 1. - t;
+        self.p0 * (mt * mt * mt)
+            + self.p1 * (3. * mt * mt * t)
+            + self.p2 * (3. * mt * t * t)
+            + self.p3 * (t * t * t)
+    }
+
+    pub fn derivative(&self, t: f32) -> Vec2 {
+        let mt = 1. - t;
+        (self.p1 - self.p0) * (3. * mt * mt)
+            + (self.p2 - self.p1) * (6. * mt * t)
+            + (self.p3 - self.p2) * (3. * t * t)
+    }
+
+    pub fn second_derivative(&self, t: f32) -> Vec2 {
+        let mt = 1. - t;
+        (self.p2 - self.p1 * 2. + self.p0) * (6. * mt)
+            + (self.p3 - self.p2 * 2. + self.p1) * (6. * t)
+    }
+
+    /// Unit tangent at `t`.
+    ///
+    /// Control points sitting on top of their handle make the derivative vanish at the ends, in
+    /// which case the direction towards the next distinct control point is used instead.
+    pub fn tangent(&self, t: f32) -> Vec2 {
+        let d = self.derivative(t);
+        if d.length_squared() > f32::EPSILON {
+            return d.normalize();
+        }
+
+        let fallback = if t < 0.5 {
+            [self.p2 - self.p0, self.p3 - self.p0]
+        } else {
+            [self.p3 - self.p1, self.p3 - self.p0]
+        };
+        fallback
+            .into_iter()
+            .find(|v| v.length_squared() > f32::EPSILON)
+            .map(Vec2::normalize)
+            .unwrap_or(Vec2::X)
+    }
+
+    /// Unit normal at `t`, pointing to the left of the direction of travel.
+    pub fn normal(&self, t: f32) -> Vec2 {
+        self.tangent(t).perp()
+    }
+
+    /// Parameter and squared distance of the point on the segment closest to `point`.
+    ///
+    /// Coarsely samples the segment, then narrows in around the best sample.
+    pub fn nearest(&self, point: Vec2) -> (f32, f32) {
+        const SAMPLES: usize = 16;
+        const REFINEMENTS: usize = 10;
+
+        let distance = |t: f32| self.position(t).distance_squared(point);
+
+        let (mut best_t, mut best_distance) = (0..=SAMPLES)
+            .map(|i| i as f32 / SAMPLES as f32)
+            .map(|t| (t, distance(t)))
+            .fold((0., f32::INFINITY), |best, candidate| {
+                if candidate.1 < best.1 {
+                    candidate
+                } else {
+                    best
+                }
+            });
+
+        let mut step = 1. / SAMPLES as f32;
+        for _ in 0..REFINEMENTS {
+            step *= 0.5;
+            for t in [best_t - step, best_t + step] {
+                let t = t.clamp(0., 1.);
+                let d = distance(t);
+                if d < best_distance {
+                    best_t = t;
+                    best_distance = d;
+                }
+            }
+        }
+
+        (best_t, best_distance)
+    }
+
+    /// Splits the segment at `t` with de Casteljau's algorithm. The two halves trace exactly the
+    /// same curve as the original.
+    pub fn split(&self, t: f32) -> (Self, Self) {
+        let p01 = self.p0.lerp(self.p1, t);
+        let p12 = self.p1.lerp(self.p2, t);
+        let p23 = self.p2.lerp(self.p3, t);
+        let p012 = p01.lerp(p12, t);
+        let p123 = p12.lerp(p23, t);
+        let p = p012.lerp(p123, t);
+
+        (
+            Self::new(self.p0, p01, p012, p),
+            Self::new(p, p123, p23, self.p3),
+        )
+    }
+
+    /// Appends points approximating the segment to within `tolerance` to `points`, leaving out
+    /// `p0`.
+    ///
+    /// Subdivides until the control points are within `tolerance` of the chord, which bounds
+    /// the distance between the curve and the chord.
+    pub fn flatten_into(&self, tolerance: f32, points: &mut Vec<Vec2>) {
+        const MAX_DEPTH: u32 = 16;
+
+        fn flatten(segment: &CubicSegment, tolerance: f32, depth: u32, points: &mut Vec<Vec2>) {
+            let chord = segment.p3 - segment.p0;
+            let distance = |p: Vec2| {
+                if chord.length_squared() > f32::EPSILON {
+                    chord.perp_dot(p - segment.p0).abs() / chord.length()
+                } else {
+                    p.distance(segment.p0)
+                }
+            };
+
+            if depth >= MAX_DEPTH
+                || (distance(segment.p1) <= tolerance && distance(segment.p2) <= tolerance)
+            {
+                points.push(segment.p3);
+            } else {
+                let (a, b) = segment.split(0.5);
+                flatten(&a, tolerance, depth + 1, points);
+                flatten(&b, tolerance, depth + 1, points);
+            }
+        }
+
+        flatten(self, tolerance.max(f32::EPSILON), 0, points);
+    }
+
+    /// Length of the curve between `t0` and `t1`, integrated with 5-point Gauss-Legendre
+    /// quadrature.
+    pub fn arc_length(&self, t0: f32, t1: f32) -> f32 {
+        const GAUSS_LEGENDRE: [(f32, f32); 5] = [
+            (0.0, 0.568_888_9),
+            (-0.538_469_3, 0.478_628_7),
+            (0.538_469_3, 0.478_628_7),
+            (-0.906_179_8, 0.236_926_9),
+            (0.906_179_8, 0.236_926_9),
+        ];
+
+        let half = (t1 - t0) * 0.5;
+        let mid = (t0 + t1) * 0.5;
+        GAUSS_LEGENDRE
+            .iter()
+            .map(|&(x, w)| w * self.derivative(mid + half * x).length())
+            .sum::<f32>()
+            * half
+    }
+
+    /// Parameters strictly inside the segment where it turns around horizontally or vertically.
+    pub fn extrema(&self) -> Vec<f32> {
+        let a = self.p1 - self.p0;
+        let b = self.p2 - self.p1;
+        let c = self.p3 - self.p2;
+        // The derivative divided by 3, as a quadratic per axis.
+        let quadratic = a - b * 2. + c;
+        let linear = (b - a) * 2.;
+
+        let mut extrema = Vec::new();
+        for axis in 0..2 {
+            let (qa, qb, qc) = (quadratic[axis], linear[axis], a[axis]);
+            if qa.abs() <= f32::EPSILON {
+                if qb.abs() > f32::EPSILON {
+                    extrema.push(-qc / qb);
+                }
+                continue;
+            }
+
+            let discriminant = qb * qb - 4. * qa * qc;
+            if discriminant >= 0. {
+                let root = discriminant.sqrt();
+                extrema.push((-qb + root) / (2. * qa));
+                extrema.push((-qb - root) / (2. * qa));
+            }
+        }
+        extrema.retain(|t| *t > 0. && *t < 1.);
+        extrema
+    }
+
+    /// Bounds of the control points, which always contain the curve.
+    pub fn hull(&self) -> Rect {
+        Rect::from_corners(
+            self.p0.min(self.p1).min(self.p2).min(self.p3),
+            self.p0.max(self.p1).max(self.p2).max(self.p3),
+        )
+    }
+
+    /// Parameter pairs where the segment crosses `other`, found by subdividing both until
+    /// their overlapping pieces are smaller than `tolerance`.
+    ///
+    /// Segments lying on top of each other touch everywhere rather than crossing, so they have
+    /// no intersections.
+    pub fn intersections(&self, other: &Self, tolerance: f32) -> Vec<(f32, f32)> {
+        const MAX_DEPTH: u32 = 24;
+        // Overlapping curves touch everywhere, which would otherwise subdivide without end.
+        const MAX_STEPS: u32 = 4096;
+        // Two distinct cubics cross at most this many times.
+        const MAX_CROSSINGS: usize = 9;
+
+        fn intersect(
+            a: (&CubicSegment, f32, f32),
+            b: (&CubicSegment, f32, f32),
+            tolerance: f32,
+            depth: u32,
+            steps: &mut u32,
+            found: &mut Vec<(f32, f32)>,
+        ) {
+            if *steps == 0 {
+                return;
+            }
+            *steps -= 1;
+
+            let (hull_a, hull_b) = (a.0.hull(), b.0.hull());
+            // Straight segments have flat hulls, so touching counts as overlapping.
+            let overlap = hull_a.min.cmple(hull_b.max).all() && hull_b.min.cmple(hull_a.max).all();
+            if !overlap {
+                return;
+            }
+
+            let small = |hull: Rect| hull.width().max(hull.height()) <= tolerance;
+            if depth >= MAX_DEPTH || (small(hull_a) && small(hull_b)) {
+                found.push(((a.1 + a.2) / 2., (b.1 + b.2) / 2.));
+                return;
+            }
+
+            // Pieces lying on top of each other would match all the way down, so give up on
+            // the whole pair the same way as when running out of steps. Pieces around a crossing
+            // come close to matching too, hence the much finer tolerance.
+            let points = |s: &CubicSegment| [s.p0, s.p1, s.p2, s.p3];
+            let (points_a, points_b) = (points(a.0), points(b.0));
+            let reversed = [points_b[3], points_b[2], points_b[1], points_b[0]];
+            let same = |b: [Vec2; 4]| {
+                points_a
+                    .iter()
+                    .zip(b)
+                    .all(|(p, q)| p.distance(q) <= tolerance * 1e-3)
+            };
+            if same(points_b) || same(reversed) {
+                *steps = 0;
+                return;
+            }
+
+            let (a0, a1) = a.0.split(0.5);
+            let (b0, b1) = b.0.split(0.5);
+            let a_mid = (a.1 + a.2) / 2.;
+            let b_mid = (b.1 + b.2) / 2.;
+            for a in [(&a0, a.1, a_mid), (&a1, a_mid, a.2)] {
+                for b in [(&b0, b.1, b_mid), (&b1, b_mid, b.2)] {
+                    intersect(a, b, tolerance, depth + 1, steps, found);
+                }
+            }
+        }
+
+        let tolerance = tolerance.max(f32::EPSILON);
+        let mut found = Vec::new();
+        let mut steps = MAX_STEPS;
+        intersect(
+            (self, 0., 1.),
+            (other, 0., 1.),
+            tolerance,
+            0,
+            &mut steps,
+            &mut found,
+        );
+        if steps == 0 {
+            return Vec::new();
+        }
+
+        // Neighbouring pieces straddling the same crossing all report it.
+        let mut intersections: Vec<(f32, f32)> = Vec::new();
+        for (ta, tb) in found {
+            let point = self.position(ta);
+            if intersections
+                .iter()
+                .all(|&(t, _)| self.position(t).distance(point) > tolerance * 2.)
+            {
+                intersections.push((ta, tb));
+            }
+        }
+        if intersections.len() > MAX_CROSSINGS {
+            return Vec::new();
+        }
+        intersections
+    }
+
+    /// Least-squares fit of a segment running from the first to the last of `points`, leaving
+    /// its ends along `tangent_start` and `tangent_end` (the latter pointing back into the
+    /// curve). Follows Schneider's "An Algorithm for Automatically Fitting Digitized Curves".
+    ///
+    /// Returns the fitted segment and the parameters it assigns to `points`, which
+    /// [`CubicSegment::max_error`] takes to measure the fit.
+    pub fn fit(points: &[Vec2], tangent_start: Vec2, tangent_end: Vec2) -> (Self, Vec<f32>) {
+        const REPARAMETERIZATIONS: usize = 4;
+
+        let mut parameters = chord_length_parameters(points);
+        let mut segment = Self::fit_parameters(points, &parameters, tangent_start, tangent_end);
+        for _ in 0..REPARAMETERIZATIONS {
+            for (u, &point) in parameters.iter_mut().zip(points) {
+                *u = segment.newton_step(point, *u);
+            }
+            segment = Self::fit_parameters(points, &parameters, tangent_start, tangent_end);
+        }
+        (segment, parameters)
+    }
+
+    /// Largest distance between `points` and the positions at their `parameters`, along with
+    /// the index of the worst point.
+    pub fn max_error(&self, points: &[Vec2], parameters: &[f32]) -> (f32, usize) {
+        points
+            .iter()
+            .zip(parameters)
+            .map(|(&point, &u)| self.position(u).distance(point))
+            .enumerate()
+            .fold((0., 0), |(max, worst), (i, error)| {
+                if error > max {
+                    (error, i)
+                } else {
+                    (max, worst)
+                }
+            })
+    }
+
+    /// Solves for the control point distances along fixed tangents that best match `points` at
+    /// `parameters`.
+    fn fit_parameters(
+        points: &[Vec2],
+        parameters: &[f32],
+        tangent_start: Vec2,
+        tangent_end: Vec2,
+    ) -> Self {
+        let (Some(&start), Some(&end)) = (points.first(), points.last()) else {
+            return Self::new(Vec2::ZERO, Vec2::ZERO, Vec2::ZERO, Vec2::ZERO);
+        };
+
+        let mut c = [[0.; 2]; 2];
+        let mut x = [0.; 2];
+        for (&point, &u) in points.iter().zip(parameters) {
+            let mu = 1. - u;
+            let b0 = mu * mu * mu;
+            let b1 = 3. * mu * mu * u;
+            let b2 = 3. * mu * u * u;
+            let b3 = u * u * u;
+
+            let a1 = tangent_start * b1;
+            let a2 = tangent_end * b2;
+            c[0][0] += a1.dot(a1);
+            c[0][1] += a1.dot(a2);
+            c[1][1] += a2.dot(a2);
+
+            let rest = point - (start * (b0 + b1) + end * (b2 + b3));
+            x[0] += a1.dot(rest);
+            x[1] += a2.dot(rest);
+        }
+        c[1][0] = c[0][1];
+
+        let det = c[0][0] * c[1][1] - c[0][1] * c[1][0];
+        let (alpha_start, alpha_end) = if det.abs() > f32::EPSILON {
+            (
+                (x[0] * c[1][1] - x[1] * c[0][1]) / det,
+                (c[0][0] * x[1] - c[1][0] * x[0]) / det,
+            )
+        } else {
+            (0., 0.)
+        };
+
+        // Degenerate or backwards solutions fall back to Schneider's heuristic of a third of
+        // the chord.
+        let chord = start.distance(end);
+        let epsilon = chord * 1e-6;
+        let (alpha_start, alpha_end) = if alpha_start < epsilon || alpha_end < epsilon {
+            (chord / 3., chord / 3.)
+        } else {
+            (alpha_start, alpha_end)
+        };
+
+        Self::new(
+            start,
+            start + tangent_start * alpha_start,
+            end + tangent_end * alpha_end,
+            end,
+        )
+    }
+
+    /// One Newton-Raphson step moving `u` towards the parameter closest to `point`.
+    fn newton_step(&self, point: Vec2, u: f32) -> f32 {
+        let offset = self.position(u) - point;
+        let d1 = self.derivative(u);
+        let d2 = self.second_derivative(u);
+        let numerator = offset.dot(d1);
+        let denominator = d1.dot(d1) + offset.dot(d2);
+        if denominator.abs() <= f32::EPSILON {
+            return u;
+        }
+        (u - numerator / denominator).clamp(0., 1.)
+    }
+}
+
+/// Fits as few cubic segments as it takes to pass within `tolerance` of every one of `points`,
+/// splitting at the worst fitting point until each piece fits.
+///
+/// Neighbouring segments share the tangent at their joint, so the result is smooth. Returns no
+/// segments if there are fewer than two distinct points.
+pub fn fit_curve(points: &[Vec2], tolerance: f32) -> Vec<CubicSegment> {
+    let points = distinct_points(points);
+
+    let mut segments = Vec::new();
+    let count = points.len();
+    if count >= 2 {
+        let tangent_start = (points[1] - points[0]).normalize_or_zero();
+        let tangent_end = (points[count - 2] - points[count - 1]).normalize_or_zero();
+        fit_curve_between(
+            &points,
+            tangent_start,
+            tangent_end,
+            tolerance,
+            &mut segments,
+        );
+    }
+    segments
+}
+
+/// Like [`fit_curve`] for points tracing a loop, ending the last segment on the first point and
+/// keeping the curve smooth where it closes.
+///
+/// The loop is always split at the point farthest from the start, so it has at least two
+/// segments and every handle of the closed spline sits on the curve once.
+pub fn fit_closed_curve(points: &[Vec2], tolerance: f32) -> Vec<CubicSegment> {
+    let mut points = distinct_points(points);
+    if let (Some(&first), Some(&last)) = (points.first(), points.last()) {
+        if first.distance_squared(last) > f32::EPSILON {
+            points.push(first);
+        }
+    }
+
+    let mut segments = Vec::new();
+    let count = points.len();
+    if count >= 4 {
+        let tangent = (points[1] - points[count - 2]).normalize_or_zero();
+        let split = (1..count - 1)
+            .max_by(|&a, &b| {
+                let distance = |i: usize| points[i].distance_squared(points[0]);
+                distance(a).total_cmp(&distance(b))
+            })
+            .unwrap();
+        let center = (points[split - 1] - points[split + 1]).normalize_or_zero();
+        fit_curve_between(&points[..=split], tangent, center, tolerance, &mut segments);
+        fit_curve_between(
+            &points[split..],
+            -center,
+            -tangent,
+            tolerance,
+            &mut segments,
+        );
+    }
+    segments
+}
+
+fn distinct_points(points: &[Vec2]) -> Vec<Vec2> {
+    let mut distinct = points.to_vec();
+    distinct.dedup_by(|a, b| a.distance_squared(*b) <= f32::EPSILON);
+    distinct
+}
+
+fn fit_curve_between(
+    points: &[Vec2],
+    tangent_start: Vec2,
+    tangent_end: Vec2,
+    tolerance: f32,
+    segments: &mut Vec<CubicSegment>,
+) {
+    let (start, end) = (points[0], points[points.len() - 1]);
+    if points.len() == 2 {
+        let third = start.distance(end) / 3.;
+        segments.push(CubicSegment::new(
+            start,
+            start + tangent_start * third,
+            end + tangent_end * third,
+            end,
+        ));
+        return;
+    }
+
+    let (segment, parameters) = CubicSegment::fit(points, tangent_start, tangent_end);
+    let (error, worst) = segment.max_error(points, &parameters);
+    if error <= tolerance {
+        segments.push(segment);
+        return;
+    }
+
+    let split = worst.clamp(1, points.len() - 2);
+    let center = (points[split - 1] - points[split + 1]).normalize_or_zero();
+    fit_curve_between(
+        &points[..=split],
+        tangent_start,
+        center,
+        tolerance,
+        segments,
+    );
+    fit_curve_between(&points[split..], -center, tangent_end, tolerance, segments);
+}
+
+/// Parameters from `0` to `1` proportional to the distance travelled along `points`.
+fn chord_length_parameters(points: &[Vec2]) -> Vec<f32> {
+    let mut parameters = Vec::with_capacity(points.len());
+    let mut total = 0.;
+    for (i, &point) in points.iter().enumerate() {
+        if i > 0 {
+            total += point.distance(points[i - 1]);
+        }
+        parameters.push(total);
+    }
+    if total > 0. {
+        for u in &mut parameters {
+            *u /= total;
+        }
+    }
+    parameters
+}
+
+/// World-space positions of a handle and its two control points.
+///
+/// `control_a` shapes the segment arriving at the handle, `control_b` the one leaving it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HandlePoints {
+    pub position: Vec2,
+    pub control_a: Vec2,
+    pub control_b: Vec2,
+}
+
+/// The shape of a spline as a chain of cubic segments.
+///
+/// Curve parameters run from `0` to `segments().len()`, the integer part selecting the segment
+/// and the fractional part being the Bézier `t` within it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SplineCurve {
+    segments: Vec<CubicSegment>,
+    closed: bool,
+}
+
+impl SplineCurve {
+    pub fn from_segments(segments: Vec<CubicSegment>, closed: bool) -> Self {
+        Self { segments, closed }
+    }
+
+    /// Builds a curve through `handles`. Closed curves get an extra segment wrapping from the
+    /// last handle back to the first.
+    pub fn from_handles(handles: &[HandlePoints], closed: bool) -> Self {
+        let segment_count = if closed {
+            handles.len()
+        } else {
+            handles.len().saturating_sub(1)
+        };
+
+        let segments = handles
+            .iter()
+            .zip(handles.iter().cycle().skip(1))
+            .take(segment_count)
+            .map(|(curr, next)| {
+                CubicSegment::new(curr.position, curr.control_b, next.control_a, next.position)
+            })
+            .collect();
+
+        Self { segments, closed }
+    }
+
+    pub fn segments(&self) -> &[CubicSegment] {
+        &self.segments
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Splits a curve parameter into a segment index and the local `t` within that segment.
+    ///
+    /// Panics if the curve is empty.
+    pub fn segment_at(&self, t: f32) -> (usize, f32) {
+        assert!(!self.is_empty(), "cannot evaluate an empty spline curve");
+
+        let last = self.segments.len() - 1;
+        let t = t.clamp(0., self.segments.len() as f32);
+        let index = (t.floor() as usize).min(last);
+        (index, t - index as f32)
+    }
+
+    pub fn position(&self, t: f32) -> Vec2 {
+        let (index, t) = self.segment_at(t);
+        self.segments[index].position(t)
+    }
+
+    pub fn derivative(&self, t: f32) -> Vec2 {
+        let (index, t) = self.segment_at(t);
+        self.segments[index].derivative(t)
+    }
+
+    pub fn second_derivative(&self, t: f32) -> Vec2 {
+        let (index, t) = self.segment_at(t);
+        self.segments[index].second_derivative(t)
+    }
+
+    pub fn tangent(&self, t: f32) -> Vec2 {
+        let (index, t) = self.segment_at(t);
+        self.segments[index].tangent(t)
+    }
+
+    pub fn normal(&self, t: f32) -> Vec2 {
+        let (index, t) = self.segment_at(t);
+        self.segments[index].normal(t)
+    }
+
+    /// Points approximating the curve to within `tolerance`. Closed curves leave out the last
+    /// point, since it's the same as the first.
+    pub fn flatten(&self, tolerance: f32) -> Vec<Vec2> {
+        let Some(first) = self.segments.first() else {
+            return Vec::new();
+        };
+
+        let mut points = vec![first.p0];
+        for segment in &self.segments {
+            segment.flatten_into(tolerance, &mut points);
+        }
+        if self.closed {
+            points.pop();
+        }
+        points
+    }
+
+    /// Curve parameter and distance of the point on the curve closest to `point`, or `None` if
+    /// the curve is empty.
+    pub fn nearest(&self, point: Vec2) -> Option<(f32, f32)> {
+        self.segments
+            .iter()
+            .enumerate()
+            .map(|(i, segment)| {
+                let (t, distance_squared) = segment.nearest(point);
+                (i as f32 + t, distance_squared)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(t, distance_squared)| (t, distance_squared.sqrt()))
+    }
+}
+
+/// Cumulative arc lengths sampled at regular parameter steps along a [`SplineCurve`].
+///
+/// Bézier parameters don't advance at constant speed, so anything that needs to travel or
+/// space things evenly along a spline goes through this table to map distances to parameters.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ArcLengthTable {
+    samples_per_segment: usize,
+    lengths: Vec<f32>,
+    closed: bool,
+}
+
+impl ArcLengthTable {
+    pub const DEFAULT_SAMPLES_PER_SEGMENT: usize = 32;
+
+    pub fn new(curve: &SplineCurve) -> Self {
+        Self::with_samples_per_segment(curve, Self::DEFAULT_SAMPLES_PER_SEGMENT)
+    }
+
+    pub fn with_samples_per_segment(curve: &SplineCurve, samples_per_segment: usize) -> Self {
+        let samples_per_segment = samples_per_segment.max(1);
+        let step = 1. / samples_per_segment as f32;
+
+        let mut lengths = Vec::with_capacity(curve.segments().len() * samples_per_segment + 1);
+        let mut length = 0.;
+        lengths.push(length);
+        for segment in curve.segments() {
+            for i in 0..samples_per_segment {
+                let t0 = i as f32 * step;
+                length += segment.arc_length(t0, t0 + step);
+                lengths.push(length);
+            }
+        }
+
+        Self {
+            samples_per_segment,
+            lengths,
+            closed: curve.is_closed(),
+        }
+    }
+
+    pub fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or_default()
+    }
+
+    /// Maps a distance along the curve to a curve parameter, clamping to the curve's ends.
+    pub fn parameter_at_distance(&self, distance: f32) -> f32 {
+        if self.lengths.len() < 2 || distance <= 0. {
+            return 0.;
+        }
+
+        let step = 1. / self.samples_per_segment as f32;
+        let last = self.lengths.len() - 1;
+        if distance >= self.length() {
+            return last as f32 * step;
+        }
+
+        let upper = self
+            .lengths
+            .partition_point(|&l| l < distance)
+            .clamp(1, last);
+        let (l0, l1) = (self.lengths[upper - 1], self.lengths[upper]);
+        let fraction = if l1 > l0 {
+            (distance - l0) / (l1 - l0)
+        } else {
+            0.
+        };
+
+        (upper - 1) as f32 * step + fraction * step
+    }
+
+    /// Maps a curve parameter to the distance travelled along the curve to reach it.
+    pub fn distance_at_parameter(&self, t: f32) -> f32 {
+        if self.lengths.len() < 2 {
+            return 0.;
+        }
+
+        let last = self.lengths.len() - 1;
+        let scaled = (t * self.samples_per_segment as f32).clamp(0., last as f32);
+        let lower = (scaled.floor() as usize).min(last - 1);
+        let fraction = scaled - lower as f32;
+
+        self.lengths[lower] + (self.lengths[lower + 1] - self.lengths[lower]) * fraction
+    }
+
+    /// Curve parameters of `count` points spaced evenly by distance.
+    ///
+    /// Open curves get a point at both ends, closed curves leave out the end since it coincides
+    /// with the start.
+    pub fn uniform_parameters(&self, count: usize) -> impl Iterator<Item = f32> + '_ {
+        let intervals = if self.closed {
+            count
+        } else {
+            count.saturating_sub(1)
+        };
+        let spacing = if intervals > 0 {
+            self.length() / intervals as f32
+        } else {
+            0.
+        };
+
+        (0..count).map(move |i| self.parameter_at_distance(i as f32 * spacing))
+    }
+}
+
+/// Even-odd test of whether `point` lies inside the closed polygon through `polygon`.
+pub fn polygon_contains(polygon: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    for (a, b) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+        if (a.y > point.y) != (b.y > point.y)
+            && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Triangulates a simple polygon by ear clipping, returning indices into `polygon`.
+///
+/// Works for either winding. Self-intersecting polygons stop triangulating once no more ears
+/// can be found, leaving part of the polygon uncovered, and polygons without area give no
+/// triangles at all.
+pub fn triangulate(polygon: &[Vec2]) -> Vec<[u32; 3]> {
+    let area = polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| a.perp_dot(*b))
+        .sum::<f32>();
+    if area.abs() <= f32::EPSILON {
+        return Vec::new();
+    }
+    // Ears are convex corners, which depends on the winding.
+    let winding = area.signum();
+
+    let mut remaining = (0..polygon.len()).collect::<Vec<_>>();
+    let mut triangles = Vec::with_capacity(polygon.len().saturating_sub(2));
+
+    while remaining.len() > 3 {
+        let count = remaining.len();
+        let ear = (0..count).find(|&i| {
+            let [a, b, c] = [
+                remaining[(i + count - 1) % count],
+                remaining[i],
+                remaining[(i + 1) % count],
+            ];
+            let (pa, pb, pc) = (polygon[a], polygon[b], polygon[c]);
+            if (pb - pa).perp_dot(pc - pb) * winding <= 0. {
+                return false;
+            }
+
+            remaining
+                .iter()
+                .filter(|&&j| j != a && j != b && j != c)
+                .all(|&j| !polygon_contains(&[pa, pb, pc], polygon[j]))
+        });
+
+        let Some(i) = ear else {
+            break;
+        };
+        triangles.push([
+            remaining[(i + count - 1) % count] as u32,
+            remaining[i] as u32,
+            remaining[(i + 1) % count] as u32,
+        ]);
+        remaining.remove(i);
+    }
+
+    if let [a, b, c] = remaining[..] {
+        let (pa, pb, pc) = (polygon[a], polygon[b], polygon[c]);
+        if (pb - pa).perp_dot(pc - pb) != 0. {
+            triangles.push([a as u32, b as u32, c as u32]);
+        }
+    }
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, TAU};
+
+    use super::*;
+
+    /// Standard cubic approximation of a unit quarter circle from (1, 0) to (0, 1).
+    fn quarter_circle() -> CubicSegment {
+        const KAPPA: f32 = 0.552_284_8;
+        CubicSegment::new(
+            Vec2::new(1., 0.),
+            Vec2::new(1., KAPPA),
+            Vec2::new(KAPPA, 1.),
+            Vec2::new(0., 1.),
+        )
+    }
+
+    fn line(start: Vec2, end: Vec2) -> CubicSegment {
+        CubicSegment::new(
+            start,
+            start.lerp(end, 1. / 3.),
+            start.lerp(end, 2. / 3.),
+            end,
+        )
+    }
+
+    fn arch() -> CubicSegment {
+        CubicSegment::new(
+            Vec2::new(0., 0.),
+            Vec2::new(0., 100.),
+            Vec2::new(100., 100.),
+            Vec2::new(100., 0.),
+        )
+    }
+
+    #[test]
+    fn split_halves_trace_the_original() {
+        let segment = arch();
+        let (first, second) = segment.split(0.3);
+
+        assert_eq!(first.p0, segment.p0);
+        assert_eq!(second.p3, segment.p3);
+        assert_eq!(first.p3, second.p0);
+        assert!(first.p3.distance(segment.position(0.3)) < 1e-4);
+        // The joint is smooth: both halves leave it in the same direction.
+        assert!(first.tangent(1.).distance(second.tangent(0.)) < 1e-4);
+
+        for i in 0..=10 {
+            let u = i as f32 / 10.;
+            assert!(first.position(u).distance(segment.position(u * 0.3)) < 1e-3);
+            assert!(second.position(u).distance(segment.position(0.3 + u * 0.7)) < 1e-3);
+        }
+    }
+
+    #[test]
+    fn arc_length_of_straight_line() {
+        let segment = line(Vec2::new(-10., 5.), Vec2::new(20., 45.));
+        assert!((segment.arc_length(0., 1.) - 50.).abs() < 1e-3);
+        assert!((segment.arc_length(0., 0.5) - 25.).abs() < 1e-3);
+    }
+
+    #[test]
+    fn arc_length_of_quarter_circle() {
+        let length = quarter_circle().arc_length(0., 1.);
+        assert!((length - FRAC_PI_2).abs() < 1e-3, "{length}");
+    }
+
+    #[test]
+    fn nearest_point_on_segment() {
+        let segment = line(Vec2::ZERO, Vec2::new(10., 0.));
+        let (t, distance_squared) = segment.nearest(Vec2::new(2.5, 3.));
+        assert!((t - 0.25).abs() < 1e-3);
+        assert!((distance_squared - 9.).abs() < 1e-3);
+
+        let (t, _) = quarter_circle().nearest(Vec2::new(2., 2.));
+        assert!((t - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn extrema_of_arch() {
+        let segment = arch();
+        let extrema = segment.extrema();
+        assert_eq!(extrema.len(), 1);
+        assert!((extrema[0] - 0.5).abs() < 1e-4);
+        assert!(segment.position(extrema[0]).distance(Vec2::new(50., 75.)) < 1e-3);
+
+        assert!(line(Vec2::ZERO, Vec2::ONE).extrema().is_empty());
+    }
+
+    #[test]
+    fn intersection_counts() {
+        let horizontal = line(Vec2::new(0., 50.), Vec2::new(100., 50.));
+        let crossings = arch().intersections(&horizontal, 0.01);
+        assert_eq!(crossings.len(), 2);
+        for (t, u) in crossings {
+            assert!(arch().position(t).distance(horizontal.position(u)) < 0.1);
+        }
+
+        let below = line(Vec2::new(0., -10.), Vec2::new(100., -10.));
+        assert!(arch().intersections(&below, 0.01).is_empty());
+
+        let diagonal = line(Vec2::ZERO, Vec2::splat(10.));
+        let crossings = diagonal.intersections(&line(Vec2::new(0., 10.), Vec2::new(10., 0.)), 0.01);
+        assert_eq!(crossings.len(), 1);
+        assert!(diagonal.position(crossings[0].0).distance(Vec2::splat(5.)) < 0.05);
+    }
+
+    #[test]
+    fn coincident_segments_do_not_intersect() {
+        let diagonal = line(Vec2::ZERO, Vec2::splat(10.));
+        for tolerance in [0.01, 0.25, 2.] {
+            assert!(arch().intersections(&arch(), tolerance).is_empty());
+            assert!(diagonal.intersections(&diagonal, tolerance).is_empty());
+            let reversed = line(Vec2::splat(10.), Vec2::ZERO);
+            assert!(diagonal.intersections(&reversed, tolerance).is_empty());
+        }
+    }
+
+    #[test]
+    fn fit_curve_stays_within_tolerance() {
+        let points = (0..=60)
+            .map(|i| {
+                let x = i as f32 * 5.;
+                Vec2::new(x, (x / 40.).sin() * 50.)
+            })
+            .collect::<Vec<_>>();
+
+        for tolerance in [0.5, 2., 8.] {
+            let segments = fit_curve(&points, tolerance);
+            assert!(!segments.is_empty());
+            assert_eq!(segments[0].p0, points[0]);
+            assert_eq!(segments[segments.len() - 1].p3, points[points.len() - 1]);
+
+            let curve = SplineCurve::from_segments(segments, false);
+            for &point in &points {
+                let (_, distance) = curve.nearest(point).unwrap();
+                assert!(distance <= tolerance * 1.01, "{distance} > {tolerance}");
+            }
+        }
+    }
+
+    #[test]
+    fn fit_closed_curve_has_at_least_two_segments() {
+        let points = (0..12)
+            .map(|i| Vec2::from_angle(i as f32 / 12. * TAU) * 5.)
+            .collect::<Vec<_>>();
+
+        // A loose tolerance would fit the whole loop with one segment.
+        let segments = fit_closed_curve(&points, 100.);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].p0, points[0]);
+        assert_eq!(segments[0].p3, segments[1].p0);
+        assert_eq!(segments[1].p3, points[0]);
+        assert!(fit_closed_curve(&points[..2], 100.).is_empty());
+    }
+
+    #[test]
+    fn triangulate_covers_a_polygon() {
+        let square = [
+            Vec2::new(0., 0.),
+            Vec2::new(1., 0.),
+            Vec2::new(1., 1.),
+            Vec2::new(0., 1.),
+        ];
+        assert_eq!(triangulate(&square).len(), 2);
+
+        let reversed = square.iter().rev().copied().collect::<Vec<_>>();
+        assert_eq!(triangulate(&reversed).len(), 2);
+    }
+
+    #[test]
+    fn triangulate_skips_polygons_without_area() {
+        let collinear = [
+            Vec2::new(0., 0.),
+            Vec2::new(1., 1.),
+            Vec2::new(2., 2.),
+            Vec2::new(3., 3.),
+            Vec2::new(1.5, 1.5),
+        ];
+        assert!(triangulate(&collinear).is_empty());
+        assert!(triangulate(&collinear[..3]).is_empty());
+        assert!(triangulate(&[Vec2::ONE; 4]).is_empty());
+    }
+
+    #[test]
+    fn fit_curve_needs_two_distinct_points() {
+        assert!(fit_curve(&[Vec2::ONE, Vec2::ONE], 1.).is_empty());
+        assert_eq!(fit_curve(&[Vec2::ZERO, Vec2::ONE], 1.).len(), 1);
+    }
+}
@@ -0,0 +1,46 @@
+pub mod asset;
+#[cfg(feature = "editor")]
+pub mod camera;
+pub mod document;
+#[cfg(feature = "editor")]
+pub mod editor;
+pub mod follow;
+pub mod geometry;
+#[cfg(any(feature = "render", feature = "svg"))]
+pub mod path;
+#[cfg(feature = "physics")]
+pub mod physics;
+#[cfg(feature = "render")]
+pub mod render;
+pub mod spline;
+#[cfg(feature = "svg")]
+pub mod svg;
+
+use bevy::{app::PluginGroupBuilder, prelude::*};
+
+use asset::SplineAssetPlugin;
+use follow::FollowSplinePlugin;
+#[cfg(feature = "physics")]
+use physics::SplinePhysicsPlugin;
+#[cfg(feature = "render")]
+use render::SplineRenderPlugin;
+use spline::SplinePlugin;
+
+/// Everything a game needs to use splines authored in the editor: the spline components,
+/// `.splines` asset loading, path following and, with the `render` and `physics` features,
+/// drawing through vello and colliders for rapier.
+pub struct SplinedPlugins;
+
+impl PluginGroup for SplinedPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        let group = PluginGroupBuilder::start::<Self>()
+            .add(SplinePlugin)
+            .add(SplineAssetPlugin)
+            .add(FollowSplinePlugin);
+        #[cfg(feature = "render")]
+        let group = group.add(SplineRenderPlugin);
+        #[cfg(feature = "physics")]
+        let group = group.add(SplinePhysicsPlugin);
+        group
+    }
+}
@@ -1,17 +1,25 @@
-mod camera;
-mod editor;
-mod spline;
+use std::path::PathBuf;
 
 use bevy::{math::vec2, prelude::*};
 
+use bevy_rapier2d::prelude::*;
 use bevy_vello::VelloPlugin;
-use camera::CameraPlugin;
-use editor::{EditorPlugin, Selected};
-use spline::{
-    Spline, SplineBundle, SplineControlPointBundle, SplineHandle, SplineHandleBundle, SplinePlugin,
+use splined::{
+    camera::CameraPlugin,
+    editor::{DocumentPath, EditorPlugin, Selected},
+    spline::{
+        ControlMode, Spline, SplineBundle, SplineControlPointBundle, SplineHandle,
+        SplineHandleBundle,
+    },
+    SplinedPlugins,
 };
 
 fn main() {
+    let open = std::env::args()
+        .skip_while(|arg| arg != "--open")
+        .nth(1)
+        .map(PathBuf::from);
+
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
@@ -23,11 +31,19 @@ fn main() {
             ..default()
         }))
         .add_plugins(VelloPlugin)
-        .add_plugins((CameraPlugin, EditorPlugin, SplinePlugin))
-        .add_systems(Startup, setup)
+        .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.))
+        .add_plugins(SplinedPlugins)
+        .add_plugins((CameraPlugin, EditorPlugin))
+        .insert_resource(DocumentPath(open))
+        .add_systems(Startup, setup.run_if(untitled))
         .run();
 }
 
+/// Whether the editor started without a document, in which case it gets some example splines.
+fn untitled(path: Res<DocumentPath>) -> bool {
+    path.0.is_none()
+}
+
 fn setup(mut commands: Commands) {
     let handle1 = commands.spawn_empty().id();
     let c1a = commands
@@ -80,7 +96,9 @@ fn setup(mut commands: Commands) {
     commands.spawn((SplineBundle {
         spline: Spline {
             handles: vec![handle1, handle2],
+            closed: true,
         },
+        control_mode: ControlMode::Aligned,
         ..default()
     },));
 }
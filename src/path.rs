@@ -0,0 +1,53 @@
+use bevy::{math::vec2, prelude::*};
+use kurbo::{BezPath, Point};
+
+use crate::geometry::SplineCurve;
+
+/// The path drawn for `curve`, in kurbo's Y-down coordinates.
+pub fn bez_path(curve: &SplineCurve) -> BezPath {
+    let mut path = BezPath::new();
+    let Some(first) = curve.segments().first() else {
+        return path;
+    };
+
+    path.move_to(SplinePoint::from(first.p0));
+    for segment in curve.segments() {
+        path.curve_to(
+            SplinePoint::from(segment.p1),
+            SplinePoint::from(segment.p2),
+            SplinePoint::from(segment.p3),
+        );
+    }
+    if curve.is_closed() {
+        path.close_path();
+    }
+
+    path
+}
+
+#[derive(Clone, Copy)]
+pub struct SplinePoint(Vec2);
+
+impl From<Vec2> for SplinePoint {
+    fn from(point: Vec2) -> Self {
+        SplinePoint(point)
+    }
+}
+
+impl From<SplinePoint> for Vec2 {
+    fn from(point: SplinePoint) -> Self {
+        point.0
+    }
+}
+
+impl From<SplinePoint> for Point {
+    fn from(point: SplinePoint) -> Self {
+        Point::new(point.0.x as f64, -point.0.y as f64)
+    }
+}
+
+impl From<Point> for SplinePoint {
+    fn from(point: Point) -> Self {
+        SplinePoint(vec2(point.x as f32, -point.y as f32))
+    }
+}
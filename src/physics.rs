@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    geometry::triangulate,
+    spline::{SplineSampler, SplineSystem},
+};
+
+/// Builds rapier colliders for splines with a [`SplineCollider`].
+///
+/// Only generates the colliders, the app still has to add `RapierPhysicsPlugin`.
+pub struct SplinePhysicsPlugin;
+
+impl Plugin for SplinePhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (update_spline_colliders, remove_spline_colliders).after(SplineSystem::UpdateSamplers),
+        );
+    }
+}
+
+/// Gives a spline a rapier [`Collider`] that follows its shape, rebuilt whenever the spline is
+/// edited.
+///
+/// Open splines become polylines, closed ones solid shapes built according to `fill`.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct SplineCollider {
+    /// Maximum distance in world units between the curve and the collider's edges.
+    pub tolerance: f32,
+    pub fill: FillCollider,
+}
+
+impl Default for SplineCollider {
+    fn default() -> Self {
+        Self {
+            tolerance: 0.5,
+            fill: FillCollider::default(),
+        }
+    }
+}
+
+/// How the inside of a closed spline is turned into a collider.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum FillCollider {
+    /// A compound of convex pieces, which is what rapier handles best.
+    #[default]
+    ConvexDecomposition,
+    /// A triangle mesh, cheaper to build for shapes that change often.
+    TriMesh,
+    /// Only the outline, leaving the inside hollow.
+    Outline,
+}
+
+/// Splines whose curve or collider settings changed since the collider was last built.
+type StaleCollider = Or<(Changed<SplineSampler>, Changed<SplineCollider>)>;
+
+fn update_spline_colliders(
+    mut commands: Commands,
+    splines: Query<(Entity, &SplineSampler, &SplineCollider), StaleCollider>,
+) {
+    for (entity, sampler, spline_collider) in splines.iter() {
+        let curve = sampler.curve();
+        let points = curve.flatten(spline_collider.tolerance);
+
+        let collider = match spline_collider.fill {
+            _ if points.len() < 2 => None,
+            _ if !curve.is_closed() => Some(Collider::polyline(points, None)),
+            _ if points.len() < 3 => Some(Collider::polyline(points, None)),
+            FillCollider::ConvexDecomposition => Some(Collider::convex_decomposition(
+                &points,
+                &outline_indices(points.len()),
+            )),
+            FillCollider::TriMesh => {
+                let triangles = triangulate(&points);
+                if triangles.is_empty() {
+                    // Outlines without area can't be triangulated, and rapier needs a triangle.
+                    let indices = outline_indices(points.len());
+                    Some(Collider::polyline(points, Some(indices)))
+                } else {
+                    Some(Collider::trimesh(points, triangles))
+                }
+            }
+            FillCollider::Outline => {
+                let indices = outline_indices(points.len());
+                Some(Collider::polyline(points, Some(indices)))
+            }
+        };
+
+        match collider {
+            Some(collider) => {
+                commands.entity(entity).insert(collider);
+            }
+            None => {
+                commands.entity(entity).remove::<Collider>();
+            }
+        }
+    }
+}
+
+fn remove_spline_colliders(
+    mut commands: Commands,
+    mut removed: RemovedComponents<SplineCollider>,
+    colliders: Query<(), With<Collider>>,
+) {
+    for entity in removed.read() {
+        if colliders.contains(entity) {
+            commands.entity(entity).remove::<Collider>();
+        }
+    }
+}
+
+/// Edges of a closed polygon with `count` vertices.
+fn outline_indices(count: usize) -> Vec<[u32; 2]> {
+    (0..count as u32)
+        .map(|i| [i, (i + 1) % count as u32])
+        .collect()
+}
@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+use bevy_vello::{
+    vello::{
+        kurbo::{Affine, Stroke},
+        peniko::{self, Fill},
+    },
+    CoordinateSpace, VelloScene,
+};
+
+use crate::{
+    path::bez_path,
+    spline::{Spline, SplineSampler, SplineStyle, SplineSystem},
+};
+
+/// Draws splines with vello, filled and stroked according to their [`SplineStyle`].
+///
+/// Rendering needs `bevy_vello::VelloPlugin`, which is left for the app to add.
+pub struct SplineRenderPlugin;
+
+impl Plugin for SplineRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (
+                add_spline_scenes,
+                render_splines.after(SplineSystem::UpdateSamplers),
+            ),
+        );
+    }
+}
+
+fn add_spline_scenes(
+    mut commands: Commands,
+    splines: Query<Entity, (With<Spline>, Without<VelloScene>)>,
+) {
+    for e in splines.iter() {
+        commands
+            .entity(e)
+            .insert((VelloScene::default(), CoordinateSpace::default()));
+    }
+}
+
+fn render_splines(
+    mut splines: Query<(&SplineSampler, &SplineStyle, &mut VelloScene), With<Spline>>,
+) {
+    for (sampler, style, mut scene) in splines.iter_mut() {
+        scene.reset();
+
+        let path = bez_path(sampler.curve());
+
+        if let Some(fill) = style.fill.filter(|_| sampler.curve().is_closed()) {
+            scene.fill(
+                Fill::EvenOdd,
+                Affine::IDENTITY,
+                peniko_color(fill),
+                None,
+                &path,
+            );
+        }
+
+        if let Some(stroke) = style.stroke {
+            scene.stroke(
+                &Stroke::new(style.stroke_width as f64),
+                Affine::IDENTITY,
+                peniko_color(stroke),
+                None,
+                &path,
+            );
+        }
+    }
+}
+
+/// Converts a bevy color to the sRGB color vello paints with.
+pub fn peniko_color(color: Color) -> peniko::Color {
+    let [r, g, b, a] = color.as_rgba_u8();
+    peniko::Color::rgba8(r, g, b, a)
+}
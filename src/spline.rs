@@ -1,25 +1,41 @@
-use bevy::{math::vec2, prelude::*};
-use bevy_vello::{
-    vello::{
-        kurbo::{Affine, BezPath, Circle, Line, Point, Stroke},
-        peniko::{self, BrushRef, Fill},
-    },
-    CoordinateSpace, VelloScene, VelloSceneBundle,
-};
+use bevy::{ecs::system::SystemParam, prelude::*, transform::TransformSystem};
+use serde::{Deserialize, Serialize};
 
-use crate::editor::Selected;
+use crate::geometry::{ArcLengthTable, HandlePoints, SplineCurve};
 
+/// Keeps control points in line with their [`ControlMode`] and [`SplineSampler`]s up to date.
 pub struct SplinePlugin;
 
 impl Plugin for SplinePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.configure_sets(
             PostUpdate,
-            (render_splines, render_handles, render_control_points),
+            (
+                SplineSystem::ApplyControlModes.before(TransformSystem::TransformPropagate),
+                SplineSystem::UpdateSamplers.after(TransformSystem::TransformPropagate),
+            ),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                apply_control_modes.in_set(SplineSystem::ApplyControlModes),
+                update_spline_samplers.in_set(SplineSystem::UpdateSamplers),
+            ),
         );
     }
 }
 
+/// The `PostUpdate` system sets of [`SplinePlugin`].
+#[derive(SystemSet, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SplineSystem {
+    /// Moves control points to satisfy their handle's [`ControlMode`]. Runs before transform
+    /// propagation.
+    ApplyControlModes,
+    /// Rebuilds the [`SplineSampler`] of splines whose shape changed. Runs after transform
+    /// propagation, so anything reading samplers should run after this.
+    UpdateSamplers,
+}
+
 #[derive(Component)]
 pub struct SplineControlPoint {
     pub handle: Entity,
@@ -28,37 +44,51 @@ pub struct SplineControlPoint {
 #[derive(Bundle)]
 pub struct SplineControlPointBundle {
     pub control_point: SplineControlPoint,
-    pub scene_bundle: VelloSceneBundle,
+    pub spatial: SpatialBundle,
 }
 
 impl SplineControlPointBundle {
     pub fn new(pos: Vec2, handle: Entity) -> Self {
         Self {
             control_point: SplineControlPoint { handle },
-            scene_bundle: VelloSceneBundle {
-                transform: Transform::from_xyz(pos.x, pos.y, 5.),
-                ..default()
-            },
+            spatial: SpatialBundle::from_transform(Transform::from_xyz(pos.x, pos.y, 5.)),
         }
     }
 }
 
-#[derive(Component, Default)]
+/// How a handle's control points are constrained, enforced by `apply_control_modes` after
+/// every edit.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
 pub enum ControlMode {
+    /// Control points point a third of the way towards the neighbouring handles, giving straight
+    /// segments.
     #[default]
     Vector,
+    /// Control points stay collinear through the handle, keeping their own lengths.
     Aligned,
+    /// Control points move independently.
     Free,
+    /// Control points are computed from the neighbouring handles for a smooth curve.
     Automatic,
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
 pub enum HandleControlMode {
+    /// Use the owning spline's [`ControlMode`].
     #[default]
     Inherit,
     Custom(ControlMode),
 }
 
+impl HandleControlMode {
+    pub fn resolve(self, spline_mode: ControlMode) -> ControlMode {
+        match self {
+            Self::Inherit => spline_mode,
+            Self::Custom(mode) => mode,
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct SplineHandle {
     pub control_point_a: Entity,
@@ -69,7 +99,7 @@ pub struct SplineHandle {
 pub struct SplineHandleBundle {
     pub handle: SplineHandle,
     pub handle_control_mode: HandleControlMode,
-    pub scene: VelloSceneBundle,
+    pub spatial: SpatialBundle,
 }
 
 impl SplineHandleBundle {
@@ -77,10 +107,7 @@ impl SplineHandleBundle {
         Self {
             handle,
             handle_control_mode: HandleControlMode::default(),
-            scene: VelloSceneBundle {
-                transform: Transform::from_xyz(pos.x, pos.y, 10.),
-                ..default()
-            },
+            spatial: SpatialBundle::from_transform(Transform::from_xyz(pos.x, pos.y, 10.)),
         }
     }
 }
@@ -88,195 +115,243 @@ impl SplineHandleBundle {
 #[derive(Component, Default)]
 pub struct Spline {
     pub handles: Vec<Entity>,
+    /// Closed splines wrap around from the last handle to the first and are filled, open ones
+    /// are only stroked.
+    pub closed: bool,
+}
+
+/// How a spline is painted. Only closed splines are filled.
+#[derive(Component, Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SplineStyle {
+    pub fill: Option<Color>,
+    pub stroke: Option<Color>,
+    pub stroke_width: f32,
+}
+
+impl Default for SplineStyle {
+    fn default() -> Self {
+        Self {
+            fill: Some(Color::rgb_u8(169, 169, 169)),
+            stroke: Some(Color::WHITE),
+            stroke_width: 1.,
+        }
+    }
 }
 
 #[derive(Bundle, Default)]
 pub struct SplineBundle {
     pub spline: Spline,
+    pub sampler: SplineSampler,
     pub control_mode: ControlMode,
+    pub style: SplineStyle,
     pub spatial: SpatialBundle,
-    pub scene: VelloScene,
-    pub coordinate_space: CoordinateSpace,
 }
 
-fn render_splines(
-    mut splines: Query<(&Spline, &mut VelloScene)>,
-    handles: Query<(&GlobalTransform, &SplineHandle)>,
-    control_points: Query<&GlobalTransform, With<SplineControlPoint>>,
-) {
-    for (spline, mut scene) in splines.iter_mut() {
-        scene.reset();
+/// Cached curve and arc length table of a [`Spline`].
+///
+/// Rebuilt only when the spline's handle list or one of its handle or control point transforms
+/// changes, so it is cheap to query every frame.
+#[derive(Component, Default)]
+pub struct SplineSampler {
+    curve: SplineCurve,
+    arc_lengths: ArcLengthTable,
+}
 
-        let handles = spline
-            .handles
-            .iter()
-            .map(|&e| {
-                let (handle_transform, handle) = handles.get(e).unwrap();
-                let control_a = control_points
-                    .get(handle.control_point_a)
-                    .unwrap()
-                    .translation()
-                    .truncate();
-                let control_b = control_points
-                    .get(handle.control_point_b)
-                    .unwrap()
-                    .translation()
-                    .truncate();
-                (
-                    handle_transform.translation().truncate(),
-                    control_a,
-                    control_b,
-                )
-            })
-            .collect::<Vec<_>>();
+impl SplineSampler {
+    pub fn new(curve: SplineCurve) -> Self {
+        let arc_lengths = ArcLengthTable::new(&curve);
+        Self { curve, arc_lengths }
+    }
 
-        let mut path = BezPath::new();
-        let first = handles.first().unwrap();
-        path.move_to(SplinePoint::from(first.0));
-        for w in handles.windows(2) {
-            match w {
-                [curr, next] => {
-                    path.curve_to(
-                        SplinePoint::from(curr.2),
-                        SplinePoint::from(next.1),
-                        SplinePoint::from(next.0),
-                    );
-                }
-                _ => unreachable!(),
-            }
-        }
-        let last = handles.last().unwrap();
-        path.curve_to(
-            SplinePoint::from(last.2),
-            SplinePoint::from(first.1),
-            SplinePoint::from(first.0),
-        );
+    pub fn curve(&self) -> &SplineCurve {
+        &self.curve
+    }
 
-        path.close_path();
+    pub fn arc_lengths(&self) -> &ArcLengthTable {
+        &self.arc_lengths
+    }
 
-        scene.fill(
-            Fill::EvenOdd,
-            Affine::IDENTITY,
-            peniko::Color::DARK_GRAY,
-            None,
-            &path,
-        );
+    pub fn length(&self) -> f32 {
+        self.arc_lengths.length()
+    }
 
-        scene.stroke(
-            &Stroke::default(),
-            Affine::IDENTITY,
-            peniko::Color::WHITE,
-            None,
-            &path,
-        );
+    pub fn parameter_at_distance(&self, distance: f32) -> f32 {
+        self.arc_lengths.parameter_at_distance(distance)
     }
-}
 
-fn render_handles(mut handles: Query<(Option<&Selected>, &mut VelloScene), With<SplineHandle>>) {
-    for (selected, mut scene) in handles.iter_mut() {
-        let selected = selected.is_some();
-        scene.reset();
-
-        let circle = Circle::new(Point::ZERO, 4.0);
-
-        scene.fill(
-            Fill::EvenOdd,
-            Affine::IDENTITY,
-            if selected {
-                peniko::Color::DARK_RED
-            } else {
-                peniko::Color::DARK_GRAY
-            },
-            None,
-            &circle,
-        );
+    pub fn position_at_distance(&self, distance: f32) -> Option<Vec2> {
+        (!self.curve.is_empty()).then(|| self.curve.position(self.parameter_at_distance(distance)))
+    }
 
-        scene.stroke(
-            &Stroke::new(1.0),
-            Affine::IDENTITY,
-            if selected {
-                peniko::Color::RED
-            } else {
-                peniko::Color::WHITE
-            },
-            None,
-            &circle,
-        );
+    pub fn tangent_at_distance(&self, distance: f32) -> Option<Vec2> {
+        (!self.curve.is_empty()).then(|| self.curve.tangent(self.parameter_at_distance(distance)))
     }
-}
 
-fn render_control_points(
-    mut control_points: Query<(
-        &SplineControlPoint,
-        &GlobalTransform,
-        Option<&Selected>,
-        &mut VelloScene,
-    )>,
-    handles: Query<&GlobalTransform, With<SplineHandle>>,
-) {
-    for (control_point, transform, selected, mut scene) in control_points.iter_mut() {
-        let Ok(handle_transform) = handles.get(control_point.handle) else {
-            continue;
-        };
-
-        let selected = selected.is_some();
-
-        scene.reset();
-
-        scene.stroke(
-            &Stroke::new(0.5),
-            Affine::IDENTITY,
-            if selected {
-                peniko::Color::RED
-            } else {
-                peniko::Color::WHITE
-            },
-            None,
-            &Line::new(
-                Point::ZERO,
-                SplinePoint::from(
-                    (-transform.translation() + handle_transform.translation()).truncate(),
-                ),
-            ),
-        );
+    /// `count` points spaced evenly by distance along the spline.
+    pub fn uniform_samples(&self, count: usize) -> Vec<Vec2> {
+        if self.curve.is_empty() {
+            return Vec::new();
+        }
 
-        scene.fill(
-            Fill::EvenOdd,
-            Affine::IDENTITY,
-            if selected {
-                peniko::Color::RED
-            } else {
-                peniko::Color::WHITE
-            },
-            None,
-            &Circle::new(Point::ZERO, 2.0),
-        );
+        self.arc_lengths
+            .uniform_parameters(count)
+            .map(|t| self.curve.position(t))
+            .collect()
     }
 }
 
-#[derive(Clone, Copy)]
-struct SplinePoint(Vec2);
+#[derive(SystemParam)]
+pub struct SplineGeometry<'w, 's> {
+    handles: Query<'w, 's, (Ref<'static, GlobalTransform>, &'static SplineHandle)>,
+    control_points: Query<'w, 's, Ref<'static, GlobalTransform>, With<SplineControlPoint>>,
+}
 
-impl From<Vec2> for SplinePoint {
-    fn from(point: Vec2) -> Self {
-        SplinePoint(point)
+impl<'w, 's> SplineGeometry<'w, 's> {
+    pub fn handle_points(&self, handle: Entity) -> Option<HandlePoints> {
+        let (handle_transform, handle) = self.handles.get(handle).ok()?;
+        let control_a = self.control_points.get(handle.control_point_a).ok()?;
+        let control_b = self.control_points.get(handle.control_point_b).ok()?;
+
+        Some(HandlePoints {
+            position: handle_transform.translation().truncate(),
+            control_a: control_a.translation().truncate(),
+            control_b: control_b.translation().truncate(),
+        })
+    }
+
+    /// Returns `None` if any of the spline's handles or control points is missing.
+    pub fn curve(&self, spline: &Spline) -> Option<SplineCurve> {
+        let handles = spline
+            .handles
+            .iter()
+            .map(|&e| self.handle_points(e))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(SplineCurve::from_handles(&handles, spline.closed))
+    }
+
+    /// Whether any of the spline's handles or control points moved since this system last ran.
+    ///
+    /// Missing entities count as a change, so callers rebuild rather than keep a stale shape.
+    pub fn changed(&self, spline: &Spline) -> bool {
+        spline.handles.iter().any(|&e| {
+            let Ok((handle_transform, handle)) = self.handles.get(e) else {
+                return true;
+            };
+
+            handle_transform.is_changed()
+                || [handle.control_point_a, handle.control_point_b]
+                    .into_iter()
+                    .any(|c| self.control_points.get(c).map_or(true, |t| t.is_changed()))
+        })
     }
 }
 
-impl From<SplinePoint> for Vec2 {
-    fn from(point: SplinePoint) -> Self {
-        point.0
+fn update_spline_samplers(
+    mut splines: Query<(Ref<Spline>, &mut SplineSampler)>,
+    geometry: SplineGeometry,
+) {
+    for (spline, mut sampler) in splines.iter_mut() {
+        if !spline.is_changed() && !geometry.changed(&spline) {
+            continue;
+        }
+
+        *sampler = geometry
+            .curve(&spline)
+            .map(SplineSampler::new)
+            .unwrap_or_default();
     }
 }
 
-impl From<SplinePoint> for Point {
-    fn from(point: SplinePoint) -> Self {
-        Point::new(point.0.x as f64, -point.0.y as f64)
+fn apply_control_modes(
+    splines: Query<(&Spline, &ControlMode)>,
+    handles: Query<(&Transform, &SplineHandle, &HandleControlMode)>,
+    mut control_points: Query<&mut Transform, (With<SplineControlPoint>, Without<SplineHandle>)>,
+) {
+    for (spline, &spline_mode) in splines.iter() {
+        let positions = spline
+            .handles
+            .iter()
+            .map(|&e| handles.get(e).ok().map(|(t, ..)| t.translation.truncate()))
+            .collect::<Vec<_>>();
+        let count = spline.handles.len();
+
+        for (i, &entity) in spline.handles.iter().enumerate() {
+            let Ok((transform, handle, handle_mode)) = handles.get(entity) else {
+                continue;
+            };
+            let Ok([mut control_a, mut control_b]) =
+                control_points.get_many_mut([handle.control_point_a, handle.control_point_b])
+            else {
+                continue;
+            };
+
+            let position = transform.translation.truncate();
+            let neighbour = |index: Option<usize>| index.and_then(|index| positions[index]);
+            let prev = neighbour(match i {
+                0 if spline.closed && count > 1 => Some(count - 1),
+                0 => None,
+                _ => Some(i - 1),
+            });
+            let next = neighbour(match i + 1 {
+                n if n < count => Some(n),
+                _ if spline.closed && count > 1 => Some(0),
+                _ => None,
+            });
+
+            match handle_mode.resolve(spline_mode) {
+                ControlMode::Free => {}
+                ControlMode::Aligned => {
+                    // Whichever control point was just moved leads, the other one follows.
+                    let b_leads = control_b.is_changed() && !control_a.is_changed();
+                    let (leader, follower) = if b_leads {
+                        (&control_b, &mut control_a)
+                    } else {
+                        (&control_a, &mut control_b)
+                    };
+
+                    let direction = (leader.translation.truncate() - position).normalize_or_zero();
+                    if direction == Vec2::ZERO {
+                        continue;
+                    }
+                    let length = follower.translation.truncate().distance(position);
+                    set_control_point(follower, position - direction * length);
+                }
+                ControlMode::Vector => {
+                    let (a, b) = match (prev, next) {
+                        (Some(prev), Some(next)) => (prev, next),
+                        (Some(prev), None) => (prev, position * 2. - prev),
+                        (None, Some(next)) => (position * 2. - next, next),
+                        (None, None) => continue,
+                    };
+                    set_control_point(&mut control_a, position + (a - position) / 3.);
+                    set_control_point(&mut control_b, position + (b - position) / 3.);
+                }
+                ControlMode::Automatic => {
+                    let (to_prev, to_next) = match (prev, next) {
+                        (Some(prev), Some(next)) => (prev - position, next - position),
+                        (Some(prev), None) => (prev - position, position - prev),
+                        (None, Some(next)) => (position - next, next - position),
+                        (None, None) => continue,
+                    };
+                    let direction = (to_next.normalize_or_zero() - to_prev.normalize_or_zero())
+                        .normalize_or_zero();
+                    set_control_point(&mut control_a, position - direction * to_prev.length() / 3.);
+                    set_control_point(&mut control_b, position + direction * to_next.length() / 3.);
+                }
+            }
+        }
     }
 }
 
-impl From<Point> for SplinePoint {
-    fn from(point: Point) -> Self {
-        SplinePoint(vec2(point.x as f32, -point.y as f32))
+/// Only writes positions that actually differ, so constraints that already hold don't keep
+/// triggering change detection.
+fn set_control_point(transform: &mut Mut<Transform>, position: Vec2) {
+    const TOLERANCE: f32 = 1e-4;
+
+    if transform.translation.truncate().distance_squared(position) > TOLERANCE * TOLERANCE {
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
     }
 }
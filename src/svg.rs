@@ -0,0 +1,634 @@
+use std::{
+    fmt::{self, Write},
+    fs,
+    path::Path,
+    str::FromStr,
+};
+
+use bevy::{prelude::*, utils::HashSet};
+use kurbo::{Affine, BezPath, Circle, Ellipse, PathEl, Point, Rect, RoundedRect, Shape};
+use roxmltree::Node;
+use thiserror::Error;
+
+use crate::{
+    document::{HandleData, SplineData, SplineDocument},
+    path::{bez_path, SplinePoint},
+    spline::{ControlMode, HandleControlMode, SplineStyle},
+};
+
+/// Tolerance used when flattening shapes like circles and rounded rects into Bézier paths.
+const SHAPE_TOLERANCE: f64 = 0.1;
+
+/// Elements whose children are never rendered directly.
+const NON_RENDERED: [&str; 7] = [
+    "defs", "clipPath", "mask", "marker", "pattern", "symbol", "style",
+];
+
+#[derive(Debug, Error)]
+pub enum SvgError {
+    #[error("could not read SVG: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse SVG: {0}")]
+    Xml(#[from] roxmltree::Error),
+}
+
+/// Writes a document as an SVG file.
+pub fn write(document: &SplineDocument, path: impl AsRef<Path>) -> Result<(), SvgError> {
+    Ok(fs::write(path, export(document))?)
+}
+
+/// Converts every spline into an SVG `<path>`, sized to fit the document's bounds.
+pub fn export(document: &SplineDocument) -> String {
+    let mut svg = String::new();
+    write_svg(&mut svg, document).expect("formatting into a String never fails");
+    svg
+}
+
+fn write_svg(svg: &mut String, document: &SplineDocument) -> fmt::Result {
+    let paths = document
+        .splines
+        .iter()
+        .map(|spline| (spline, bez_path(&spline.curve())))
+        .collect::<Vec<_>>();
+
+    let bounds = paths
+        .iter()
+        .filter(|(_, path)| !path.elements().is_empty())
+        .map(|(spline, path)| {
+            // Leave room for the half of the stroke outside the path.
+            let stroke_width = spline
+                .style
+                .stroke
+                .map_or(0., |_| spline.style.stroke_width);
+            let margin = stroke_width as f64 / 2.;
+            path.bounding_box().inflate(margin, margin)
+        })
+        .reduce(|a, b| a.union(b))
+        .unwrap_or(Rect::ZERO);
+
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+        bounds.x0,
+        bounds.y0,
+        bounds.width(),
+        bounds.height(),
+        bounds.width(),
+        bounds.height(),
+    )?;
+    if let Some(title) = &document.metadata.title {
+        writeln!(svg, "  <title>{}</title>", escape(title))?;
+    }
+    if let Some(description) = &document.metadata.description {
+        writeln!(svg, "  <desc>{}</desc>", escape(description))?;
+    }
+
+    let mut ids = HashSet::new();
+    for (spline, path) in &paths {
+        write!(svg, "  <path")?;
+        if let Some(name) = &spline.name {
+            write!(svg, r#" id="{}""#, escape(&unique_id(&mut ids, name)))?;
+        }
+        write!(svg, r#" d="{}""#, path.to_svg())?;
+
+        // Open splines are never filled when rendered.
+        let fill = spline.style.fill.filter(|_| spline.closed);
+        write_paint(svg, "fill", fill)?;
+        write_paint(svg, "stroke", spline.style.stroke)?;
+        if spline.style.stroke.is_some() {
+            write!(svg, r#" stroke-width="{}""#, spline.style.stroke_width)?;
+        }
+        writeln!(svg, "/>")?;
+    }
+
+    writeln!(svg, "</svg>")
+}
+
+/// Ids have to be unique within the file, while names don't. Every subpath of an imported path
+/// shares its name, so repeats get a numbered suffix.
+fn unique_id(ids: &mut HashSet<String>, name: &str) -> String {
+    let id = (1..)
+        .map(|n| match n {
+            1 => name.to_owned(),
+            n => format!("{name}-{n}"),
+        })
+        .find(|id| !ids.contains(id))
+        .expect("there are more suffixes than ids");
+    ids.insert(id.clone());
+    id
+}
+
+fn write_paint(svg: &mut String, name: &str, color: Option<Color>) -> fmt::Result {
+    let Some(color) = color else {
+        return write!(svg, r#" {name}="none""#);
+    };
+
+    let [r, g, b, a] = color.as_rgba_u8();
+    write!(svg, r##" {name}="#{r:02x}{g:02x}{b:02x}""##)?;
+    if a < u8::MAX {
+        write!(svg, r#" {name}-opacity="{}""#, color.a())?;
+    }
+    Ok(())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Reads the shapes of an SVG file as a spline document.
+pub fn read(path: impl AsRef<Path>) -> Result<SplineDocument, SvgError> {
+    import(&fs::read_to_string(path)?)
+}
+
+/// Converts every path and basic shape in an SVG into splines, one per subpath.
+///
+/// Transforms, including the one mapping the root's `viewBox` onto its size, are baked into the
+/// handle positions and the Y axis is flipped, so the drawing keeps its orientation in world space.
+pub fn import(svg: &str) -> Result<SplineDocument, SvgError> {
+    let xml = roxmltree::Document::parse(svg)?;
+    let root = xml.root_element();
+
+    let mut document = SplineDocument::default();
+    import_node(
+        root,
+        Inherited {
+            transform: viewport_transform(root),
+            ..default()
+        },
+        &mut document.splines,
+    );
+    Ok(document)
+}
+
+/// Maps the root element's `viewBox` onto its `width` and `height` the way
+/// `preserveAspectRatio` asks for.
+fn viewport_transform(root: Node) -> Affine {
+    use svgtypes::Align;
+
+    let Some(view_box) = root
+        .attribute("viewBox")
+        .and_then(|v| svgtypes::ViewBox::from_str(v).ok())
+    else {
+        return Affine::IDENTITY;
+    };
+    // Without an absolute size there's no viewport to fit, so the viewBox is used as is.
+    let size = |name: &str, fallback: f64| {
+        root.attribute(name)
+            .and_then(|v| svgtypes::Length::from_str(v).ok())
+            .and_then(absolute_length)
+            .unwrap_or(fallback)
+    };
+    let (width, height) = (size("width", view_box.w), size("height", view_box.h));
+    let aspect = root
+        .attribute("preserveAspectRatio")
+        .and_then(|a| svgtypes::AspectRatio::from_str(a).ok())
+        .unwrap_or_default();
+
+    let origin = Affine::translate((-view_box.x, -view_box.y));
+    let (sx, sy) = (width / view_box.w, height / view_box.h);
+    let (ax, ay) = match aspect.align {
+        Align::None => return Affine::scale_non_uniform(sx, sy) * origin,
+        Align::XMinYMin => (0., 0.),
+        Align::XMidYMin => (0.5, 0.),
+        Align::XMaxYMin => (1., 0.),
+        Align::XMinYMid => (0., 0.5),
+        Align::XMidYMid => (0.5, 0.5),
+        Align::XMaxYMid => (1., 0.5),
+        Align::XMinYMax => (0., 1.),
+        Align::XMidYMax => (0.5, 1.),
+        Align::XMaxYMax => (1., 1.),
+    };
+    let scale = if aspect.slice { sx.max(sy) } else { sx.min(sy) };
+    let offset = (
+        (width - view_box.w * scale) * ax,
+        (height - view_box.h * scale) * ay,
+    );
+    Affine::translate(offset) * Affine::scale(scale) * origin
+}
+
+/// A length in user units, for units that don't depend on fonts or the viewport.
+fn absolute_length(length: svgtypes::Length) -> Option<f64> {
+    use svgtypes::LengthUnit;
+    let scale = match length.unit {
+        LengthUnit::None | LengthUnit::Px => 1.,
+        LengthUnit::In => 96.,
+        LengthUnit::Cm => 96. / 2.54,
+        LengthUnit::Mm => 96. / 25.4,
+        LengthUnit::Pt => 96. / 72.,
+        LengthUnit::Pc => 16.,
+        _ => return None,
+    };
+    Some(length.number * scale)
+}
+
+/// Presentation state inherited from ancestor elements.
+#[derive(Clone, Copy)]
+struct Inherited {
+    transform: Affine,
+    /// The stroke width is kept in user units, since it's scaled by the transform of whichever
+    /// element ends up using it.
+    style: SplineStyle,
+}
+
+impl Default for Inherited {
+    fn default() -> Self {
+        Self {
+            transform: Affine::IDENTITY,
+            // SVG's initial values: black fill, no stroke.
+            style: SplineStyle {
+                fill: Some(Color::BLACK),
+                stroke: None,
+                stroke_width: 1.,
+            },
+        }
+    }
+}
+
+fn import_node(node: Node, inherited: Inherited, splines: &mut Vec<SplineData>) {
+    if NON_RENDERED.contains(&node.tag_name().name()) || attribute(node, "display") == Some("none")
+    {
+        return;
+    }
+
+    let transform = node
+        .attribute("transform")
+        .and_then(|t| svgtypes::Transform::from_str(t).ok())
+        .map_or(inherited.transform, |t| {
+            inherited.transform * Affine::new([t.a, t.b, t.c, t.d, t.e, t.f])
+        });
+    let inherited = Inherited {
+        transform,
+        style: style(node, inherited.style),
+    };
+
+    if let Some(path) = shape_path(node) {
+        let name = node.attribute("id").map(str::to_owned);
+        let style = SplineStyle {
+            stroke_width: inherited.style.stroke_width
+                * transform.determinant().abs().sqrt() as f32,
+            ..inherited.style
+        };
+        splines.extend(
+            splines_from_path(&(transform * path), style)
+                .into_iter()
+                .map(|spline| SplineData {
+                    name: name.clone(),
+                    ..spline
+                }),
+        );
+    }
+
+    for child in node.children().filter(Node::is_element) {
+        import_node(child, inherited, splines);
+    }
+}
+
+/// Looks a presentation attribute up in the `style` attribute first, then as a plain attribute.
+fn attribute<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attribute("style")
+        .and_then(|style| {
+            style.split(';').find_map(|declaration| {
+                let (key, value) = declaration.split_once(':')?;
+                (key.trim() == name).then_some(value.trim())
+            })
+        })
+        .or_else(|| node.attribute(name))
+}
+
+fn number(node: Node, name: &str) -> f64 {
+    node.attribute(name)
+        .and_then(|v| svgtypes::Length::from_str(v).ok())
+        .map_or(0., |l| l.number)
+}
+
+fn style(node: Node, inherited: SplineStyle) -> SplineStyle {
+    let paint = |name: &str, opacity: &str, inherited: Option<Color>| {
+        let color = match attribute(node, name).map(svgtypes::Paint::from_str) {
+            Some(Ok(svgtypes::Paint::None)) => None,
+            Some(Ok(svgtypes::Paint::Color(c))) => {
+                Some(Color::rgba_u8(c.red, c.green, c.blue, c.alpha))
+            }
+            _ => inherited,
+        };
+        let opacity = attribute(node, opacity)
+            .and_then(|o| o.parse::<f32>().ok())
+            .unwrap_or(1.);
+        color.map(|c| c.with_a(c.a() * opacity))
+    };
+
+    let stroke_width = attribute(node, "stroke-width")
+        .and_then(|w| svgtypes::Length::from_str(w).ok())
+        .map_or(inherited.stroke_width, |w| w.number as f32);
+
+    SplineStyle {
+        fill: paint("fill", "fill-opacity", inherited.fill),
+        stroke: paint("stroke", "stroke-opacity", inherited.stroke),
+        stroke_width,
+    }
+}
+
+/// The outline of a path or basic shape element in its own user space.
+fn shape_path(node: Node) -> Option<BezPath> {
+    match node.tag_name().name() {
+        "path" => {
+            let d = node.attribute("d")?;
+            BezPath::from_svg(d)
+                .map_err(|err| warn!("Skipping SVG path with invalid data: {err}"))
+                .ok()
+        }
+        "rect" => {
+            let (x, y) = (number(node, "x"), number(node, "y"));
+            let rect = Rect::new(x, y, x + number(node, "width"), y + number(node, "height"));
+            // Either radius defaults to the other. Elliptical corners aren't supported, so
+            // they're rounded with the smaller of the two.
+            let (rx, ry) = (number(node, "rx"), number(node, "ry"));
+            let radius = if rx > 0. && ry > 0. {
+                rx.min(ry)
+            } else {
+                rx.max(ry)
+            };
+            if radius > 0. {
+                Some(RoundedRect::from_rect(rect, radius).to_path(SHAPE_TOLERANCE))
+            } else {
+                Some(rect.to_path(SHAPE_TOLERANCE))
+            }
+        }
+        "circle" => Some(
+            Circle::new((number(node, "cx"), number(node, "cy")), number(node, "r"))
+                .to_path(SHAPE_TOLERANCE),
+        ),
+        "ellipse" => Some(
+            Ellipse::new(
+                (number(node, "cx"), number(node, "cy")),
+                (number(node, "rx"), number(node, "ry")),
+                0.,
+            )
+            .to_path(SHAPE_TOLERANCE),
+        ),
+        name @ ("polygon" | "polyline") => {
+            let mut points = svgtypes::PointsParser::from(node.attribute("points")?);
+            let mut path = BezPath::new();
+            path.move_to(points.next()?);
+            for point in points {
+                path.line_to(point);
+            }
+            if name == "polygon" {
+                path.close_path();
+            }
+            Some(path)
+        }
+        _ => None,
+    }
+}
+
+/// Splits a path into splines at every move, turning its segments into handles.
+fn splines_from_path(path: &BezPath, style: SplineStyle) -> Vec<SplineData> {
+    let mut splines = Vec::new();
+    let mut handles = Vec::<HandleData>::new();
+    let mut finish = |handles: &mut Vec<HandleData>, closed: bool| {
+        let handles = std::mem::take(handles);
+        if handles.len() > 1 {
+            splines.push(SplineData {
+                name: None,
+                closed,
+                // Free keeps the control points exactly where the SVG put them.
+                control_mode: ControlMode::Free,
+                style,
+                handles,
+            });
+        }
+    };
+
+    let point = |p: Point| Vec2::from(SplinePoint::from(p));
+    // Where the current subpath started, for segments continuing after a close.
+    let mut start = Vec2::ZERO;
+
+    for element in path.elements() {
+        if !matches!(element, PathEl::MoveTo(_) | PathEl::ClosePath) && handles.is_empty() {
+            handles.push(corner(start));
+        }
+
+        match *element {
+            PathEl::MoveTo(p) => {
+                finish(&mut handles, false);
+                start = point(p);
+                handles.push(corner(start));
+            }
+            PathEl::LineTo(p) => line_to(&mut handles, point(p)),
+            PathEl::QuadTo(q, p) => {
+                let (q, p) = (point(q), point(p));
+                let p0 = handles.last().map_or(start, |h| h.position);
+                // Degree elevation: a quadratic is a cubic with controls 2/3 of the way to q.
+                cubic_to(&mut handles, p0.lerp(q, 2. / 3.), p.lerp(q, 2. / 3.), p);
+            }
+            PathEl::CurveTo(c1, c2, p) => cubic_to(&mut handles, point(c1), point(c2), point(p)),
+            PathEl::ClosePath => {
+                close(&mut handles);
+                finish(&mut handles, true);
+            }
+        }
+    }
+    finish(&mut handles, false);
+
+    splines
+}
+
+/// A handle with both control points on top of it.
+fn corner(position: Vec2) -> HandleData {
+    HandleData {
+        position,
+        control_a: position,
+        control_b: position,
+        mode: HandleControlMode::Inherit,
+    }
+}
+
+fn cubic_to(handles: &mut Vec<HandleData>, c1: Vec2, c2: Vec2, p: Vec2) {
+    if let Some(last) = handles.last_mut() {
+        last.control_b = c1;
+    }
+    handles.push(HandleData {
+        control_a: c2,
+        ..corner(p)
+    });
+}
+
+/// Straight segments get their control points a third of the way along, like
+/// [`ControlMode::Vector`] would place them.
+fn line_to(handles: &mut Vec<HandleData>, p: Vec2) {
+    let p0 = handles.last().map_or(p, |h| h.position);
+    cubic_to(handles, p0.lerp(p, 1. / 3.), p.lerp(p0, 1. / 3.), p);
+}
+
+fn close(handles: &mut Vec<HandleData>) {
+    let (Some(first), Some(last)) = (handles.first().copied(), handles.last().copied()) else {
+        return;
+    };
+    if handles.len() < 2 {
+        return;
+    }
+
+    // Paths usually end on their starting point before closing, in which case the last handle
+    // is the first one coming back around.
+    if last.position.distance(first.position) < 1e-3 {
+        handles.pop();
+        handles[0].control_a = last.control_a;
+    } else {
+        handles[0].control_a = first.position.lerp(last.position, 1. / 3.);
+        if let Some(last) = handles.last_mut() {
+            last.control_b = last.position.lerp(first.position, 1. / 3.);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(spline: &SplineData) -> Vec<Vec2> {
+        spline.handles.iter().map(|h| h.position).collect()
+    }
+
+    #[test]
+    fn import_bakes_nested_transforms() {
+        let document = import(
+            r#"<svg xmlns="http://www.w3.org/2000/svg">
+                <g transform="translate(10, 20)">
+                    <rect width="10" height="5" transform="scale(2)"/>
+                </g>
+            </svg>"#,
+        )
+        .unwrap();
+
+        assert_eq!(document.splines.len(), 1);
+        let spline = &document.splines[0];
+        assert!(spline.closed);
+        assert_eq!(
+            positions(spline),
+            [
+                Vec2::new(10., -20.),
+                Vec2::new(30., -20.),
+                Vec2::new(30., -30.),
+                Vec2::new(10., -30.),
+            ]
+        );
+    }
+
+    #[test]
+    fn import_maps_view_box_onto_viewport() {
+        // Scaled by 2 to fit the width, then centered vertically in the taller viewport.
+        let document = import(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="10 10 50 50" width="100" height="200">
+                <polyline points="10,10 60,60"/>
+            </svg>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            positions(&document.splines[0]),
+            [Vec2::new(0., -50.), Vec2::new(100., -150.)]
+        );
+
+        let document = import(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 50 50" width="100" height="200" preserveAspectRatio="none">
+                <polyline points="0,0 50,50"/>
+            </svg>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            positions(&document.splines[0]),
+            [Vec2::ZERO, Vec2::new(100., -200.)]
+        );
+    }
+
+    #[test]
+    fn inherited_stroke_width_scales_with_the_element_using_it() {
+        let document = import(
+            r##"<svg xmlns="http://www.w3.org/2000/svg">
+                <g stroke="#000000" stroke-width="2" transform="scale(2)">
+                    <path d="M0 0 L10 0"/>
+                    <path d="M0 0 L10 0" transform="scale(3)"/>
+                    <path d="M0 0 L10 0" stroke-width="1"/>
+                </g>
+            </svg>"##,
+        )
+        .unwrap();
+
+        let widths = document
+            .splines
+            .iter()
+            .map(|s| s.style.stroke_width)
+            .collect::<Vec<_>>();
+        assert_eq!(widths, [4., 12., 2.]);
+    }
+
+    #[test]
+    fn export_gives_every_path_a_unique_id() {
+        let document = import(
+            r#"<svg xmlns="http://www.w3.org/2000/svg">
+                <path id="shape" d="M0 0 L10 0 L10 10 Z M20 0 L30 0 L30 10 Z"/>
+                <path id="shape-2" d="M0 20 L10 20"/>
+            </svg>"#,
+        )
+        .unwrap();
+        assert_eq!(document.splines.len(), 3);
+
+        let svg = export(&document);
+        let ids = svg
+            .split(r#" id=""#)
+            .skip(1)
+            .filter_map(|rest| rest.split('"').next())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["shape", "shape-2", "shape-2-2"]);
+    }
+
+    #[test]
+    fn export_round_trip() {
+        // Placed with its top left corner on the origin and without a stroke, so the exported
+        // viewBox maps straight onto the viewport.
+        let document = SplineDocument {
+            splines: vec![SplineData {
+                name: Some("outline".into()),
+                closed: true,
+                control_mode: ControlMode::Free,
+                style: SplineStyle {
+                    fill: Some(Color::rgb_u8(40, 80, 120)),
+                    stroke: None,
+                    stroke_width: 1.,
+                },
+                handles: vec![
+                    HandleData {
+                        position: Vec2::new(0., 0.),
+                        control_a: Vec2::new(0., -20.),
+                        control_b: Vec2::new(30., 0.),
+                        mode: HandleControlMode::Inherit,
+                    },
+                    HandleData {
+                        position: Vec2::new(100., 0.),
+                        control_a: Vec2::new(70., -10.),
+                        control_b: Vec2::new(100., -25.),
+                        mode: HandleControlMode::Inherit,
+                    },
+                    HandleData {
+                        position: Vec2::new(100., -50.),
+                        control_a: Vec2::new(100., -40.),
+                        control_b: Vec2::new(60.5, -50.),
+                        mode: HandleControlMode::Inherit,
+                    },
+                    HandleData {
+                        position: Vec2::new(0., -50.),
+                        control_a: Vec2::new(30.25, -45.),
+                        control_b: Vec2::new(0., -30.),
+                        mode: HandleControlMode::Inherit,
+                    },
+                ],
+            }],
+            ..default()
+        };
+
+        let imported = import(&export(&document)).unwrap();
+        assert_eq!(imported.splines, document.splines);
+    }
+}